@@ -0,0 +1,51 @@
+//! prints the hash keyauth's hash check expects for a built binary, and registers it if a seller key is given
+//! (registering needs the `seller` feature).
+//! ```sh
+//! cargo run --example hash -- target/release/my-app
+//! cargo run --example hash --features seller -- target/release/my-app "seller key" https://keyauth.win/api/seller/
+//! ```
+
+use keyauth_obf::integrity::file_hash;
+#[cfg(feature = "seller")]
+use keyauth_obf::seller::SellerApi;
+
+#[tokio::main]
+async fn main() {
+    let mut args = std::env::args().skip(1);
+    let path = match args.next() {
+        Some(path) => path,
+        None => {
+            eprintln!("usage: hash <binary> [seller key] [seller api url]");
+            std::process::exit(2);
+        }
+    };
+    let hash = match file_hash(&path) {
+        Ok(hash) => hash,
+        Err(e) => {
+            eprintln!("couldnt hash {}: {}", path, e);
+            std::process::exit(1);
+        }
+    };
+    println!("{}", hash);
+
+    if let Some(seller_key) = args.next() {
+        register(&hash, &seller_key, args.next()).await;
+    }
+}
+
+/// adds the hash to the application of the seller key
+#[cfg(feature = "seller")]
+async fn register(hash: &str, seller_key: &str, api_url: Option<String>) {
+    let api_url = api_url.unwrap_or_else(|| "https://keyauth.win/api/seller/".to_string());
+    let seller = SellerApi::new(seller_key, &api_url);
+    if let Err(msg) = seller.add_hash(hash).await.inner() {
+        eprintln!("couldnt register the hash: {}", msg);
+        std::process::exit(1);
+    }
+}
+
+#[cfg(not(feature = "seller"))]
+async fn register(_hash: &str, _seller_key: &str, _api_url: Option<String>) {
+    eprintln!("registering the hash needs the seller feature, run it with --features seller");
+    std::process::exit(2);
+}
@@ -0,0 +1,1031 @@
+//! the client behind every version module's `KeyauthApi`
+//!
+//! the endpoints are the same in every api version, only how a request is encoded and how a response is checked
+//! differs. that part is the [`Protocol`] each version module implements, everything else lives here once.
+
+use crate::chat::{ChatError, ChatMessage};
+use crate::client::{KeyauthClient, LogError};
+use crate::credentials::Credential;
+use crate::events::{AuthEvent, Hook, Hooks, DEFAULT_EXPIRY_WARNING};
+use crate::online::{parse_users, OnlineUser, PresenceTracker};
+use crate::session::{AppInfo, UserSession};
+use crate::trace::RequestSpan;
+use crate::transport::{self, Body, Data, Resp};
+use crate::two_factor::{LoginError, TwoFactorSetup};
+#[cfg(feature = "web_loader")]
+use crate::web_loader::{LoaderServer, WebLoaderConfig, WebLoginError};
+use crate::Res;
+use async_trait::async_trait;
+use base16::decode;
+use futures_util::stream::BoxStream;
+use goldberg::goldberg_stmts;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+use zeroize::Zeroizing;
+
+/// what an api version does differently, implemented by the version modules
+#[async_trait]
+pub trait Protocol: Clone + Default + Send + Sync + 'static {
+    /// what encode passes on to verify for the same request, the iv for 1.0
+    type Nonce: Send;
+
+    /// a new session is started with `init`, 1.0 and 1.2 pick a new session key here and send it along
+    fn start_session(&mut self, _init: &mut Data) {}
+
+    /// turns the request fields into the body that is sent, `init` is set for the init request
+    fn encode(&self, data: &Data, init: bool) -> (Body, Self::Nonce);
+
+    /// checks the response and returns its json body, Err is why the response was rejected
+    fn verify(&self, resp: &Resp, nonce: Self::Nonce, init: bool) -> Result<String, String>;
+
+    /// keyauth accepted a login or license, the response already passed verify
+    async fn logged_in(&self, _resp: &Resp, _username: &str, _hwid: &str) {}
+
+    /// keyauth answered a login or license with success false, `message` is what it said
+    async fn login_rejected(&self, _message: &str) {}
+}
+
+/// why a request didnt return a response that can be used
+#[derive(Debug)]
+pub(crate) enum RequestError {
+    Network(String),
+    InvalidApplication,
+    /// the response failed the version's check, contains why
+    Tampered(String),
+    /// the response passed the check but isnt json
+    Invalid(String),
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestError::Network(msg) => write!(f, "{}", msg),
+            RequestError::InvalidApplication => write!(f, "The application doesn't exist"),
+            RequestError::Tampered(why) => write!(f, "response was tampered with: {}", why),
+            RequestError::Invalid(resp) => write!(f, "invalid response: {}", resp),
+        }
+    }
+}
+
+impl From<RequestError> for String {
+    fn from(err: RequestError) -> Self {
+        err.to_string()
+    }
+}
+
+impl From<RequestError> for LogError {
+    fn from(err: RequestError) -> Self {
+        match err {
+            RequestError::InvalidApplication => LogError::Rejected(err.to_string()),
+            _ => LogError::NotSent(err.to_string()),
+        }
+    }
+}
+
+impl From<RequestError> for LoginError {
+    fn from(err: RequestError) -> Self {
+        match err {
+            RequestError::Tampered(_) => LoginError::Tampered,
+            err => LoginError::Other(err.to_string()),
+        }
+    }
+}
+
+/// a response that passed the version's check
+pub(crate) struct Reply {
+    pub(crate) json: serde_json::Value,
+    pub(crate) resp: Resp,
+}
+
+/// every function in this struct (accept log and ban) returns a Result with the server message as error,
+/// a response that fails the api version's check returns Err("response was tampered with: ...").
+/// clones share the credentials and the session key instead of copying them
+#[derive(Default, Clone)]
+pub struct KeyauthApi<P> {
+    pub(crate) name: Credential,
+    pub(crate) owner_id: Credential,
+    version: String,
+    session_id: Credential,
+    pub api_url: String,
+    pub num_keys: String,
+    pub num_online_users: String,
+    pub num_users: String,
+    pub app_version: String,
+    pub customer_panel_link: String,
+    pub username: String,
+    pub ip: String,
+    pub hwid: String,
+    pub create_date: String,
+    pub last_login: String,
+    pub subscription: String,
+    pub message: String,
+    pub success: bool,
+    pub blacklisted: bool,
+    pub response: String,
+    pub(crate) user: Option<UserSession>,
+    pub(crate) hooks: Hooks,
+    /// how long before a subscription runs out the hooks get SubscriptionExpiring on login
+    pub expiry_warning: Duration,
+    /// where web_login and button listen for the web loader
+    #[cfg(feature = "web_loader")]
+    pub web_loader: WebLoaderConfig,
+    pub(crate) protocol: P,
+}
+
+impl<P: Protocol> KeyauthApi<P> {
+    /// the defaults the version modules' constructors start from
+    pub(crate) fn with_protocol(
+        protocol: P,
+        name: Credential,
+        owner_id: Credential,
+        version: &str,
+        api_url: &str,
+    ) -> Self {
+        let res: Self = goldberg_stmts! {{
+            nodebug!();
+        Self {
+            name: name,
+            owner_id: owner_id,
+            version: version.to_string(),
+            session_id: Credential::default(),
+            api_url: api_url.to_string(),
+            num_keys: String::new(),
+            num_online_users: String::new(),
+            num_users: String::new(),
+            app_version: version.to_string(),
+            customer_panel_link: String::new(),
+            username: String::new(),
+            ip: String::new(),
+            hwid: machine_uuid::get(),
+            create_date: String::new(),
+            last_login: String::new(),
+            subscription: String::new(),
+            message: String::new(),
+            success: false,
+            blacklisted: false,
+            response: String::new(),
+            user: None,
+            hooks: Hooks::default(),
+            expiry_warning: DEFAULT_EXPIRY_WARNING,
+            #[cfg(feature = "web_loader")]
+            web_loader: WebLoaderConfig::default(),
+            protocol: protocol,
+        }}};
+        res
+    }
+
+    /// registers a hook that is called with every AuthEvent, see the events module
+    pub fn on_event<F: Fn(&AuthEvent) + Send + Sync + 'static>(&mut self, hook: F) {
+        self.hooks.push(Arc::new(hook));
+    }
+
+    /// initializes a session, **required to run before any other function in this struct!!!** accept new
+    pub async fn init(&mut self, hash: Option<&str>) -> Res<AppInfo> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+            let mut data = Data(Vec::new());
+            data.insert("type", "init");
+            if let Some(hash) = hash {
+                data.insert("hash", hash);
+            }
+            data.insert("ver", &self.version);
+            data.insert("name", self.name.reveal().as_str());
+            data.insert("ownerid", self.owner_id.reveal().as_str());
+            self.protocol.start_session(&mut data);
+
+        let json_rep = match self.request(data, true).await {
+            Ok(reply) => reply.json,
+            Err(err) => return Res(Err(err.into())),
+        };
+            nodebug!();
+        if json_rep["success"].as_bool().unwrap_or(false) {
+            self.session_id = Credential::from(json_rep["sessionid"].as_str().unwrap_or("").to_string());
+            let app = self.set_app_info(&json_rep);
+            self.hooks.emit(AuthEvent::Initialized(app.clone()));
+            Res(Ok(app))
+        } else {
+            let message = json_rep["message"].as_str().unwrap_or("").to_string();
+            if message == "invalidver" {
+                if let Some(download_url) = json_rep["download"].as_str() {
+                    if !download_url.is_empty() {
+                        let _ = webbrowser::open(download_url);
+                    }
+                }
+            }
+            Res(Err(message))
+        }}};
+        res
+    }
+
+    /// registeres a new user
+    pub async fn register(
+        &mut self,
+        username: String,
+        password: String,
+        license: String,
+        hwid: Option<String>,
+    ) -> Res<UserSession> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+        let password = Zeroizing::new(password);
+        let license = Zeroizing::new(license);
+        let hwidd = match hwid {
+            Some(hwid) => hwid,
+            None => machine_uuid::get(),
+        };
+            let mut req_data = Data(Vec::new());
+            req_data.insert("type", "register");
+            req_data.insert("username", &username);
+            req_data.insert("pass", password.as_str());
+            req_data.insert("key", license.as_str());
+            req_data.insert("hwid", &hwidd);
+
+        let json_rep = match self.session_request(req_data).await {
+            Ok(json_rep) => json_rep,
+            Err(err) => return Res(Err(err.into())),
+        };
+            nodebug!();
+        if json_rep["success"].as_bool().unwrap_or(false) {
+            let session = self.set_user_info(&json_rep, &username, &hwidd);
+            self.hooks.emit(AuthEvent::LicenseActivated);
+            Res(Ok(session))
+        } else {
+            Res(Err(json_rep["message"].as_str().unwrap_or("").to_string()))
+        }}};
+        res
+    }
+
+    /// upgrades a user license level or extends a license
+    pub async fn upgrade(&self, username: String, license: String) -> Res<()> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+        let license = Zeroizing::new(license);
+            let mut req_data = Data(Vec::new());
+            req_data.insert("type", "upgrade");
+            req_data.insert("username", &username);
+            req_data.insert("key", license.as_str());
+
+        match self.session_request(req_data).await {
+            Ok(json_rep) => {
+                if json_rep["success"].as_bool().unwrap_or(false) {
+                    self.hooks.emit(AuthEvent::LicenseActivated);
+                }
+                Self::unit_result(&json_rep)
+            }
+            Err(err) => Res(Err(err.into())),
+        }}};
+        res
+    }
+
+    /// login self explanatory, `code` is the 2fa code.
+    /// returns LoginError::TwoFactorRequired if the user has 2fa enabled and no code was sent
+    pub async fn login(
+        &mut self,
+        username: String,
+        password: String,
+        hwid: Option<String>,
+        code: Option<String>,
+    ) -> Res<UserSession, LoginError> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+        let password = Zeroizing::new(password);
+        let hwidd = match hwid {
+            Some(hwid) => hwid,
+            None => machine_uuid::get(),
+        };
+            let mut req_data = Data(Vec::new());
+            req_data.insert("type", "login");
+            req_data.insert("username", &username);
+            req_data.insert("pass", password.as_str());
+            req_data.insert("hwid", &hwidd);
+            if let Some(code) = &code {
+                req_data.insert("code", code);
+            }
+
+        let reply = match self.session_reply(req_data).await {
+            Ok(reply) => reply,
+            Err(err) => return Res(Err(err.into())),
+        };
+            nodebug!();
+        if reply.json["success"].as_bool().unwrap_or(false) {
+            self.protocol.logged_in(&reply.resp, &username, &hwidd).await;
+            Res(Ok(self.set_user_info(&reply.json, &username, &hwidd)))
+        } else {
+            let message = reply.json["message"].as_str().unwrap_or("").to_string();
+            self.protocol.login_rejected(&message).await;
+            Res(Err(LoginError::from(message)))
+        }}};
+        res
+    }
+
+    /// <https://docs.keyauth.cc/api/license>
+    pub async fn license(&mut self, license: String, hwid: Option<String>) -> Res<UserSession> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+        let license = Zeroizing::new(license);
+        let hwidd = match hwid {
+            Some(hwid) => hwid,
+            None => machine_uuid::get(),
+        };
+            let mut req_data = Data(Vec::new());
+            req_data.insert("type", "license");
+            req_data.insert("key", license.as_str());
+            req_data.insert("hwid", &hwidd);
+
+        let reply = match self.session_reply(req_data).await {
+            Ok(reply) => reply,
+            Err(err) => return Res(Err(err.into())),
+        };
+            nodebug!();
+        if reply.json["success"].as_bool().unwrap_or(false) {
+            self.protocol.logged_in(&reply.resp, &license, &hwidd).await;
+            let session = self.set_user_info(&reply.json, &license, &hwidd);
+            self.hooks.emit(AuthEvent::LicenseActivated);
+            Res(Ok(session))
+        } else {
+            let message = reply.json["message"].as_str().unwrap_or("").to_string();
+            self.protocol.login_rejected(&message).await;
+            Res(Err(message))
+        }}};
+        res
+    }
+
+    /// this will get a global variable (not user) and return it
+    pub async fn var(&self, varid: String) -> Res<String> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+            let mut req_data = Data(Vec::new());
+            req_data.insert("type", "var");
+            req_data.insert("varid", &varid);
+
+        match self.session_request(req_data).await {
+            Ok(json_rep) => Self::message_result(&json_rep),
+            Err(err) => Res(Err(err.into())),
+        }}};
+        res
+    }
+
+    /// downloads a file, and decodes using base16::decode
+    pub async fn file(&self, fileid: String) -> Res<Vec<u8>> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+            let mut req_data = Data(Vec::new());
+            req_data.insert("type", "file");
+            req_data.insert("fileid", &fileid);
+
+        let json_rep = match self.session_request(req_data).await {
+            Ok(json_rep) => json_rep,
+            Err(err) => return Res(Err(err.into())),
+        };
+            nodebug!();
+        if json_rep["success"].as_bool().unwrap_or(false) {
+            match decode(json_rep["contents"].as_str().unwrap_or("")) {
+                Ok(contents) => Res(Ok(contents)),
+                Err(e) => Res(Err(e.to_string())),
+            }
+        } else {
+            Res(Err(json_rep["message"].as_str().unwrap_or("").to_string()))
+        }}};
+        res
+    }
+
+    /// sends a webhook from keyauth's servers so the url isnt exposed
+    pub async fn webhook(&self, webid: String, params: String) -> Res<String> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+            let mut req_data = Data(Vec::new());
+            req_data.insert("type", "webhook");
+            req_data.insert("webid", &webid);
+            req_data.insert("params", &params);
+
+        match self.session_request(req_data).await {
+            Ok(json_rep) => Self::message_result(&json_rep),
+            Err(err) => Res(Err(err.into())),
+        }}};
+        res
+    }
+
+    /// checks if the user is blacklisted and sets self.blacklisted acordingly
+    pub async fn checkblacklist(&mut self) -> Res<()> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+        let mut req_data = Data(Vec::new());
+        req_data.insert("type", "checkblacklist");
+        req_data.insert("hwid", &self.hwid);
+
+        match self.session_request(req_data).await {
+            Ok(json_rep) => {
+                self.blacklisted = json_rep["success"].as_bool().unwrap_or(false);
+                if self.blacklisted {
+                    self.hooks.emit(AuthEvent::Blacklisted);
+                }
+                Res(Ok(()))
+            }
+            Err(err) => Res(Err(err.into())),
+        }}};
+        res
+    }
+
+    /// checks if the session is still active or if it expired
+    pub async fn check_session(&self) -> Res<bool> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+        let mut req_data = Data(Vec::new());
+        req_data.insert("type", "check");
+
+        match self.session_request(req_data).await {
+            Ok(json_rep) => {
+                let active = json_rep["success"].as_bool().unwrap_or(false);
+                if !active {
+                    self.hooks.emit(AuthEvent::SessionExpired);
+                }
+                Res(Ok(active))
+            }
+            Err(err) => Res(Err(err.into())),
+        }}};
+        res
+    }
+
+    /// gets json of online users
+    pub async fn fetch_online(&self) -> Res<serde_json::Value> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+        let mut req_data = Data(Vec::new());
+        req_data.insert("type", "fetchOnline");
+
+        let json_rep = match self.session_request(req_data).await {
+            Ok(json_rep) => json_rep,
+            Err(err) => return Res(Err(err.into())),
+        };
+        if json_rep["success"].as_bool().unwrap_or(false) {
+            Res(Ok(json_rep["users"].clone()))
+        } else {
+            Res(Err(json_rep["message"].as_str().unwrap_or("").to_string()))
+        }}};
+        res
+    }
+
+    /// gets the arry of messages in a channel
+    pub async fn get_chat(&self, channel: String) -> Res<serde_json::Value> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+        let mut req_data = Data(Vec::new());
+        req_data.insert("type", "chatget");
+        req_data.insert("channel", &channel);
+
+        let json_rep = match self.session_request(req_data).await {
+            Ok(json_rep) => json_rep,
+            Err(err) => return Res(Err(err.into())),
+        };
+        if json_rep["success"].as_bool().unwrap_or(false) {
+            Res(Ok(json_rep["messages"].clone()))
+        } else {
+            Res(Err(json_rep["message"].as_str().unwrap_or("").to_string()))
+        }}};
+        res
+    }
+
+    /// same as get_chat but returns the messages typed
+    pub async fn get_chat_messages(&self, channel: String) -> Res<Vec<ChatMessage>, ChatError> {
+        KeyauthClient::get_chat_messages(self, channel).await
+    }
+
+    /// polls a channel and yields the new messages, see KeyauthClient::chat_stream
+    pub fn chat_stream(
+        &self,
+        channel: String,
+        poll_interval: Duration,
+    ) -> BoxStream<'_, Res<ChatMessage, ChatError>> {
+        KeyauthClient::chat_stream(self, channel, poll_interval)
+    }
+
+    /// sends a chat message in a channel, failures are returned as a typed ChatError
+    pub async fn send_chat_message(&self, channel: String, message: String) -> Res<(), ChatError> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+        let mut req_data = Data(Vec::new());
+        req_data.insert("type", "chatsend");
+        req_data.insert("channel", &channel);
+        req_data.insert("message", &message);
+
+        let reply = match self.session_reply(req_data).await {
+            Ok(reply) => reply,
+            Err(err) => return Res(Err(ChatError::Other(err.into()))),
+        };
+            nodebug!();
+        if reply.json["success"].as_bool().unwrap_or(false) {
+            Res(Ok(()))
+        } else {
+            let err = ChatError::from(reply.json["message"].as_str().unwrap_or("").to_string());
+            let retry_after = reply.resp.head.get("retry-after").and_then(|h| h.to_str().ok());
+            Res(Err(err.with_retry_after(retry_after)))
+        }}};
+        res
+    }
+
+    /// self explanatory
+    pub async fn ban(&self) {
+        goldberg_stmts! {{
+            nodebug!();
+        let mut req_data = Data(Vec::new());
+        req_data.insert("type", "ban");
+        let _ = self.session_request(req_data).await;}};
+    }
+
+    /// sets a user variable to varvalue
+    pub async fn setvar(&mut self, varname: String, varvalue: String) -> Res<()> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+        let mut req_data = Data(Vec::new());
+        req_data.insert("type", "setvar");
+        req_data.insert("var", &varname);
+        req_data.insert("data", &varvalue);
+
+        match self.session_request(req_data).await {
+            Ok(json_rep) => {
+                self.message = json_rep["message"].as_str().unwrap_or("").to_string();
+                self.success = json_rep["success"].as_bool().unwrap_or(false);
+                Res(Ok(()))
+            }
+            Err(err) => Res(Err(err.into())),
+        }}};
+        res
+    }
+
+    /// gets a user variable
+    pub async fn getvar(&self, varname: String) -> Res<String> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+        let mut req_data = Data(Vec::new());
+        req_data.insert("type", "getvar");
+        req_data.insert("var", &varname);
+
+        let json_rep = match self.session_request(req_data).await {
+            Ok(json_rep) => json_rep,
+            Err(err) => return Res(Err(err.into())),
+        };
+        if json_rep["success"].as_bool().unwrap_or(false) {
+            Res(Ok(json_rep["response"].as_str().unwrap_or("").to_string()))
+        } else {
+            Res(Err(json_rep["message"].as_str().unwrap_or("").to_string()))
+        }}};
+        res
+    }
+
+    /// logs somethink to keyauth
+    pub async fn log(&self, message: String, pcuser: Option<String>) {
+        let _ = self.try_log(message, pcuser).await;
+    }
+
+    /// like log but returns Err when the message couldnt be sent or keyauth didnt accept it
+    pub async fn try_log(&self, message: String, pcuser: Option<String>) -> Res<(), LogError> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+        let usr = match pcuser {
+            Some(pcuser) => pcuser,
+            None => self.username.clone(),
+        };
+        let mut req_data = Data(Vec::new());
+        req_data.insert("type", "log");
+        req_data.insert("message", &message);
+        req_data.insert("pcuser", &usr);
+
+        match self.session_request(req_data).await {
+            Ok(json_rep) => Res(Self::unit_result(&json_rep).inner().map_err(LogError::Rejected)),
+            Err(err) => Res(Err(err.into())),
+        }}};
+        res
+    }
+
+    /// changes Username,
+    pub async fn change_username(&mut self, new_username: String) -> Res<String> {
+        let res: Res<String> = goldberg_stmts! {{
+            nodebug!();
+        let mut req_data = Data(Vec::new());
+        req_data.insert("type", "changeUsername");
+        req_data.insert("newUsername", &new_username);
+
+        match self.session_request(req_data).await {
+            Ok(json_rep) => Self::message_result(&json_rep),
+            Err(err) => Res(Err(err.into())),
+        }}};
+        res
+    }
+
+    /// starts 2fa enrollment for the logged in user, returns what the authenticator app needs.
+    /// 2fa is only enabled once confirm_2fa is called with a code from the app
+    pub async fn enable_2fa(&self) -> Res<TwoFactorSetup> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+        let mut req_data = Data(Vec::new());
+        req_data.insert("type", "2faenable");
+
+        let json_rep = match self.session_request(req_data).await {
+            Ok(json_rep) => json_rep,
+            Err(err) => return Res(Err(err.into())),
+        };
+            nodebug!();
+        if json_rep["success"].as_bool().unwrap_or(false) {
+            Res(TwoFactorSetup::from_response(&json_rep, &self.name.reveal(), &self.username))
+        } else {
+            Res(Err(json_rep["message"].as_str().unwrap_or("").to_string()))
+        }}};
+        res
+    }
+
+    /// finishes 2fa enrollment with a code from the authenticator app
+    pub async fn confirm_2fa(&self, code: String) -> Res<()> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+        let mut req_data = Data(Vec::new());
+        req_data.insert("type", "2faenable");
+        req_data.insert("code", &code);
+
+        match self.session_request(req_data).await {
+            Ok(json_rep) => Self::unit_result(&json_rep),
+            Err(err) => Res(Err(err.into())),
+        }}};
+        res
+    }
+
+    /// disables 2fa for the logged in user, needs a current code from the authenticator app
+    pub async fn disable_2fa(&self, code: String) -> Res<()> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+        let mut req_data = Data(Vec::new());
+        req_data.insert("type", "2fadisable");
+        req_data.insert("code", &code);
+
+        match self.session_request(req_data).await {
+            Ok(json_rep) => Self::unit_result(&json_rep),
+            Err(err) => Res(Err(err.into())),
+        }}};
+        res
+    }
+
+    /// ends the session so it stops counting as an online user, init has to be called again before any other function
+    pub async fn logout(&mut self) -> Res<()> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+        let mut req_data = Data(Vec::new());
+        req_data.insert("type", "logout");
+
+        let json_rep = match self.session_request(req_data).await {
+            Ok(json_rep) => json_rep,
+            Err(err) => return Res(Err(err.into())),
+        };
+            nodebug!();
+        if json_rep["success"].as_bool().unwrap_or(false) {
+            self.session_id = Credential::default();
+            self.clear_user_info();
+            Res(Ok(()))
+        } else {
+            Res(Err(json_rep["message"].as_str().unwrap_or("").to_string()))
+        }}};
+        res
+    }
+
+    /// sends a password reset email to the user if `email` matches the one on their account, returns the server message
+    pub async fn forgot_password(&self, username: String, email: String) -> Res<String> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+        let mut req_data = Data(Vec::new());
+        req_data.insert("type", "forgot");
+        req_data.insert("username", &username);
+        req_data.insert("email", &email);
+
+        match self.session_request(req_data).await {
+            Ok(json_rep) => Self::message_result(&json_rep),
+            Err(err) => Res(Err(err.into())),
+        }}};
+        res
+    }
+
+    /// refreshes num_users, num_keys, num_online_users, app_version and customer_panel_link
+    pub async fn fetch_stats(&mut self) -> Res<AppInfo> {
+        let res = goldberg_stmts! {{
+            nodebug!();
+        let mut req_data = Data(Vec::new());
+        req_data.insert("type", "fetchStats");
+
+        let json_rep = match self.session_request(req_data).await {
+            Ok(json_rep) => json_rep,
+            Err(err) => return Res(Err(err.into())),
+        };
+            nodebug!();
+        if json_rep["success"].as_bool().unwrap_or(false) {
+            Res(Ok(self.set_app_info(&json_rep)))
+        } else {
+            Res(Err(json_rep["message"].as_str().unwrap_or("").to_string()))
+        }}};
+        res
+    }
+
+    /// waits for the keyauth web loader to send a handshake to the local server (see self.web_loader) and logs in with it.
+    /// returns the session of the user, if keyauth rejects the login it is returned as WebLoginError::Rejected.
+    /// WARNING THIS FUNCTION ISNT OBFUSCATED DUE TO ERRORS
+    #[cfg(feature = "web_loader")]
+    pub async fn web_login(&mut self, hwid: Option<String>) -> Res<UserSession, WebLoginError> {
+        let hwidd = match hwid {
+            Some(hwid) => hwid,
+            None => self.hwid.clone(),
+        };
+
+        let server = match LoaderServer::bind(&self.web_loader).await {
+            Ok(server) => server,
+            Err(err) => return Res(Err(err)),
+        };
+        let handshake = match server.wait_for("/handshake").await {
+            Ok(handshake) => handshake,
+            Err(err) => return Res(Err(err)),
+        };
+        let (user, token) = match (handshake.query.get("user"), handshake.query.get("token")) {
+            (Some(user), Some(token)) if !user.is_empty() && !token.is_empty() => {
+                (user.clone(), token.clone())
+            }
+            _ => {
+                let _ = handshake.respond(400, "missing user or token").await;
+                return Res(Err(WebLoginError::InvalidHandshake));
+            }
+        };
+
+        let mut req_data = Data(Vec::new());
+        req_data.insert("type", "login");
+        req_data.insert("username", &user);
+        req_data.insert("token", &token);
+        req_data.insert("hwid", &hwidd);
+
+        let json_rep = match self.session_request(req_data).await {
+            Ok(json_rep) => json_rep,
+            Err(err) => {
+                let _ = handshake.respond(500, &err.to_string()).await;
+                return Res(Err(match err {
+                    RequestError::Tampered(_) => WebLoginError::Tampered,
+                    RequestError::Network(msg) => WebLoginError::Network(msg),
+                    RequestError::Invalid(resp) => WebLoginError::InvalidResponse(resp),
+                    err => WebLoginError::Rejected(err.to_string()),
+                }));
+            }
+        };
+        let message = json_rep["message"].as_str().unwrap_or("").to_string();
+        if json_rep["success"].as_bool().unwrap_or(false) {
+            let session = self.set_user_info(&json_rep, &user, &hwidd);
+            let _ = handshake.respond(200, &message).await;
+            Res(Ok(session))
+        } else {
+            let _ = handshake.respond(401, &message).await;
+            Res(Err(WebLoginError::Rejected(message)))
+        }
+    }
+
+    /// waits for the web loader to press `button` (a request to /`button` on the local server)
+    #[cfg(feature = "web_loader")]
+    pub async fn button(&self, button: &str) -> Res<()> {
+        let res = goldberg_stmts! {{
+        let server = match LoaderServer::bind(&self.web_loader).await {
+            Ok(server) => server,
+            Err(err) => return Res(Err(err.to_string())),
+        };
+        match server.wait_for(&format!("/{}", button)).await {
+            Ok(req) => {
+                let _ = req.respond(200, "OK").await;
+                Res(Ok(()))
+            }
+            Err(err) => Res(Err(err.to_string())),
+        }}};
+        res
+    }
+
+    /// builds the session from a login response and fills the loose user fields from it
+    pub(crate) fn set_user_info(
+        &mut self,
+        json_rep: &serde_json::Value,
+        username: &str,
+        hwid: &str,
+    ) -> UserSession {
+        let session = UserSession::from_response(json_rep, username, hwid);
+        self.username = session.username.clone();
+        self.ip = session.ip.clone();
+        self.hwid = session.hwid.clone();
+        self.create_date = session.create_date.to_string();
+        self.last_login = session.last_login.to_string();
+        self.subscription = session.subscription().to_string();
+        self.user = Some(session.clone());
+        self.hooks.logged_in(&session, self.expiry_warning);
+        session
+    }
+
+    /// undoes set_user_info, the loose user fields go back to what they were before the login
+    fn clear_user_info(&mut self) {
+        self.username = String::new();
+        self.ip = String::new();
+        self.hwid = machine_uuid::get();
+        self.create_date = String::new();
+        self.last_login = String::new();
+        self.subscription = String::new();
+        self.user = None;
+    }
+
+    /// builds the app info from an init or fetchStats response and fills the loose app fields from it
+    fn set_app_info(&mut self, json_rep: &serde_json::Value) -> AppInfo {
+        let app = AppInfo::from_response(json_rep);
+        self.num_keys = app.num_keys.to_string();
+        self.num_online_users = app.num_online_users.to_string();
+        self.num_users = app.num_users.to_string();
+        if !app.version.is_empty() {
+            self.app_version = app.version.clone();
+        }
+        self.customer_panel_link = app.customer_panel_link.clone();
+        app
+    }
+
+    fn unit_result(json_rep: &serde_json::Value) -> Res<()> {
+        if json_rep["success"].as_bool().unwrap_or(false) {
+            Res(Ok(()))
+        } else {
+            Res(Err(json_rep["message"].as_str().unwrap_or("").to_string()))
+        }
+    }
+
+    fn message_result(json_rep: &serde_json::Value) -> Res<String> {
+        let message = json_rep["message"].as_str().unwrap_or("").to_string();
+        if json_rep["success"].as_bool().unwrap_or(false) {
+            Res(Ok(message))
+        } else {
+            Res(Err(message))
+        }
+    }
+
+    /// adds the session and application to the request, sends it and returns the json response
+    async fn session_request(&self, req_data: Data) -> Result<serde_json::Value, RequestError> {
+        self.session_reply(req_data).await.map(|reply| reply.json)
+    }
+
+    /// same as session_request but keeps the response, for its headers
+    async fn session_reply(&self, mut req_data: Data) -> Result<Reply, RequestError> {
+        req_data.insert("sessionid", self.session_id.reveal().as_str());
+        req_data.insert("name", self.name.reveal().as_str());
+        req_data.insert("ownerid", self.owner_id.reveal().as_str());
+        self.request(req_data, false).await
+    }
+
+    /// encodes the request the way the api version wants it, sends it and returns the response if it passes the version's check
+    async fn request(&self, req_data: Data, init: bool) -> Result<Reply, RequestError> {
+        // the span is made from the plaintext, 1.0 encrypts the body
+        let span = RequestSpan::new(req_data.params());
+        let (body, nonce) = self.protocol.encode(&req_data, init);
+        drop(req_data);
+        let resp = match transport::send(body, &self.api_url, &span).await {
+            Ok(resp) => resp,
+            Err(msg) => return Err(RequestError::Network(msg)),
+        };
+        if resp.res == "KeyAuth_Invalid" {
+            span.failed("invalid_application", &resp.res);
+            return Err(RequestError::InvalidApplication);
+        }
+        let body = match self.protocol.verify(&resp, nonce, init) {
+            Ok(body) => body,
+            Err(why) => {
+                let err = RequestError::Tampered(why);
+                span.failed("tampered", &err.to_string());
+                self.hooks.emit(AuthEvent::Tampered);
+                return Err(err);
+            }
+        };
+        match serde_json::from_str(&body) {
+            Ok(json) => {
+                span.json(&json);
+                Ok(Reply { json, resp })
+            }
+            Err(_) => {
+                span.failed("invalid_response", &body);
+                Err(RequestError::Invalid(body))
+            }
+        }
+    }
+}
+
+/// forwards to the inherent methods
+#[async_trait]
+impl<P: Protocol> KeyauthClient for KeyauthApi<P> {
+    async fn init(&mut self, hash: Option<&str>) -> Res<AppInfo> {
+        KeyauthApi::init(self, hash).await
+    }
+    async fn register(
+        &mut self,
+        username: String,
+        password: String,
+        license: String,
+        hwid: Option<String>,
+    ) -> Res<UserSession> {
+        KeyauthApi::register(self, username, password, license, hwid).await
+    }
+    async fn upgrade(&self, username: String, license: String) -> Res<()> {
+        KeyauthApi::upgrade(self, username, license).await
+    }
+    async fn login(
+        &mut self,
+        username: String,
+        password: String,
+        hwid: Option<String>,
+        code: Option<String>,
+    ) -> Res<UserSession, LoginError> {
+        KeyauthApi::login(self, username, password, hwid, code).await
+    }
+    async fn license(&mut self, license: String, hwid: Option<String>) -> Res<UserSession> {
+        KeyauthApi::license(self, license, hwid).await
+    }
+    async fn var(&self, varid: String) -> Res<String> {
+        KeyauthApi::var(self, varid).await
+    }
+    async fn file(&self, fileid: String) -> Res<Vec<u8>> {
+        KeyauthApi::file(self, fileid).await
+    }
+    async fn webhook(&self, webid: String, params: String) -> Res<String> {
+        KeyauthApi::webhook(self, webid, params).await
+    }
+    async fn checkblacklist(&mut self) -> Res<()> {
+        KeyauthApi::checkblacklist(self).await
+    }
+    async fn check_session(&self) -> Res<bool> {
+        KeyauthApi::check_session(self).await
+    }
+    async fn fetch_online(&self) -> Res<serde_json::Value> {
+        KeyauthApi::fetch_online(self).await
+    }
+    async fn get_chat(&self, channel: String) -> Res<serde_json::Value> {
+        KeyauthApi::get_chat(self, channel).await
+    }
+    async fn send_chat_message(&self, channel: String, message: String) -> Res<(), ChatError> {
+        KeyauthApi::send_chat_message(self, channel, message).await
+    }
+    async fn ban(&self) {
+        KeyauthApi::ban(self).await
+    }
+    async fn setvar(&mut self, varname: String, varvalue: String) -> Res<()> {
+        KeyauthApi::setvar(self, varname, varvalue).await
+    }
+    async fn getvar(&self, varname: String) -> Res<String> {
+        KeyauthApi::getvar(self, varname).await
+    }
+    async fn log(&self, message: String, pcuser: Option<String>) {
+        KeyauthApi::log(self, message, pcuser).await
+    }
+    async fn try_log(&self, message: String, pcuser: Option<String>) -> Res<(), LogError> {
+        KeyauthApi::try_log(self, message, pcuser).await
+    }
+    async fn change_username(&mut self, new_username: String) -> Res<String> {
+        KeyauthApi::change_username(self, new_username).await
+    }
+    async fn enable_2fa(&self) -> Res<TwoFactorSetup> {
+        KeyauthApi::enable_2fa(self).await
+    }
+    async fn confirm_2fa(&self, code: String) -> Res<()> {
+        KeyauthApi::confirm_2fa(self, code).await
+    }
+    async fn disable_2fa(&self, code: String) -> Res<()> {
+        KeyauthApi::disable_2fa(self, code).await
+    }
+    async fn logout(&mut self) -> Res<()> {
+        KeyauthApi::logout(self).await
+    }
+    async fn forgot_password(&self, username: String, email: String) -> Res<String> {
+        KeyauthApi::forgot_password(self, username, email).await
+    }
+    async fn fetch_stats(&mut self) -> Res<AppInfo> {
+        KeyauthApi::fetch_stats(self).await
+    }
+
+    fn user(&self) -> Option<&UserSession> {
+        self.user.as_ref()
+    }
+    fn username(&self) -> &str {
+        &self.username
+    }
+    fn subscription(&self) -> &str {
+        &self.subscription
+    }
+
+    async fn fetch_online_users(&mut self) -> Res<Vec<OnlineUser>> {
+        let users = match KeyauthApi::fetch_online(self).await.inner() {
+            Ok(users) => users,
+            Err(msg) => return Res(Err(msg)),
+        };
+        let users = match parse_users(users) {
+            Ok(users) => users,
+            Err(msg) => return Res(Err(msg)),
+        };
+        // one entry per session, the same count as numOnlineUsers and PresenceTracker::count
+        self.num_online_users = users.len().to_string();
+        Res(Ok(users))
+    }
+
+    fn presence_tracker(&self) -> PresenceTracker {
+        PresenceTracker::new(self.num_online_users.parse().unwrap_or(0))
+    }
+
+    fn on_event(&mut self, hook: Hook) {
+        KeyauthApi::on_event(self, move |event: &AuthEvent| hook(event))
+    }
+}
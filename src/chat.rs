@@ -0,0 +1,196 @@
+/*!
+typed chat models shared by the api versions
+
+the raw `chatget` response is an array of `{ author, message, timestamp }` objects, this module turns it into [`ChatMessage`]s
+and turns the server failure messages into [`ChatError`]s so you dont have to match on server text.
+*/
+
+use serde::{Deserialize, Deserializer};
+use std::collections::HashSet;
+use std::fmt;
+use std::time::Duration;
+
+/// a single message in a chat channel
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+pub struct ChatMessage {
+    pub author: String,
+    pub message: String,
+    /// unix timestamp (seconds) of when the message was sent
+    #[serde(deserialize_with = "de_timestamp")]
+    pub timestamp: u64,
+}
+
+/// keyauth sends the timestamp as a string on some versions and as a number on others
+fn de_timestamp<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Timestamp {
+        Num(u64),
+        Str(String),
+    }
+    match Timestamp::deserialize(deserializer)? {
+        Timestamp::Num(n) => Ok(n),
+        Timestamp::Str(s) => s.trim().parse().map_err(serde::de::Error::custom),
+    }
+}
+
+/// errors returned by the chat functions
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChatError {
+    /// the server is rate limiting chat requests, `retry_after` is set when the server told us how long to wait
+    RateLimited { retry_after: Option<Duration> },
+    /// the user is muted, `until` is the unix timestamp (seconds) the mute ends at, None if the server didnt send a
+    /// time that could be read
+    Muted { until: Option<u64> },
+    /// the message is longer than the server allows
+    MessageTooLong,
+    /// the channel doesnt exist for this application
+    ChannelNotFound,
+    /// any other error, contains the message returned by the server
+    Other(String),
+}
+
+impl fmt::Display for ChatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChatError::RateLimited {
+                retry_after: Some(d),
+            } => {
+                write!(f, "rate limited, retry after {}s", d.as_secs())
+            }
+            ChatError::RateLimited { retry_after: None } => write!(f, "rate limited"),
+            ChatError::Muted { until: Some(until) } => write!(f, "muted until {}", until),
+            ChatError::Muted { until: None } => write!(f, "muted"),
+            ChatError::MessageTooLong => write!(f, "message too long"),
+            ChatError::ChannelNotFound => write!(f, "channel not found"),
+            ChatError::Other(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ChatError {}
+
+impl From<String> for ChatError {
+    /// maps the message the server sent back to a typed error
+    fn from(msg: String) -> Self {
+        let lower = msg.to_lowercase();
+        if lower.contains("muted") {
+            // searched in the bytes of msg, an index into the lowercased string can be off for non ascii text.
+            // "until " is ascii so the index after it is a char boundary of msg
+            let until = msg
+                .as_bytes()
+                .windows(6)
+                .position(|w| w.eq_ignore_ascii_case(b"until "))
+                .and_then(|i| parse_time(&msg[i + 6..]));
+            ChatError::Muted { until }
+        } else if lower.contains("too long") {
+            ChatError::MessageTooLong
+        } else if lower.contains("channel")
+            && (lower.contains("not found")
+                || lower.contains("not exist")
+                || lower.contains("doesn't exist"))
+        {
+            ChatError::ChannelNotFound
+        } else if lower.contains("delay limit")
+            || lower.contains("rate limit")
+            || lower.contains("too many requests")
+        {
+            ChatError::RateLimited { retry_after: None }
+        } else {
+            ChatError::Other(msg)
+        }
+    }
+}
+
+impl ChatError {
+    /// fills in `retry_after` of a RateLimited error from the value of a `Retry-After` header (seconds)
+    pub fn with_retry_after(self, header: Option<&str>) -> Self {
+        match self {
+            ChatError::RateLimited { retry_after: None } => ChatError::RateLimited {
+                retry_after: header
+                    .and_then(|h| h.trim().parse::<u64>().ok())
+                    .map(Duration::from_secs),
+            },
+            err => err,
+        }
+    }
+}
+
+/// reads the end of a mute, keyauth sends a unix timestamp or a date like `2024-05-01 13:00:00` or `05/01/2024 13:00`
+/// (utc). anything after the time (a trailing dot, a timezone...) is ignored
+fn parse_time(text: &str) -> Option<u64> {
+    let text = text.trim().trim_end_matches('.');
+    let mut parts = text.split_whitespace();
+    let date = parts.next()?;
+    if let Ok(secs) = date.parse::<u64>() {
+        return Some(secs);
+    }
+    let fields: Vec<u64> = date
+        .split(['-', '/'])
+        .map(|field| field.parse().ok())
+        .collect::<Option<_>>()?;
+    let (year, month, day) = match fields[..] {
+        [year, month, day] if date.contains('-') => (year, month, day),
+        [month, day, year] => (year, month, day),
+        _ => return None,
+    };
+    // bounded so the calculation below cant overflow on a made up date
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) || !(1970..=9999).contains(&year) {
+        return None;
+    }
+    let time: Vec<u64> = match parts.next() {
+        Some(time) => time
+            .split(':')
+            .map(|field| field.parse().ok())
+            .collect::<Option<_>>()?,
+        None => Vec::new(),
+    };
+    let (hour, minute, second) = match time[..] {
+        [] => (0, 0, 0),
+        [hour, minute] => (hour, minute, 0),
+        [hour, minute, second] => (hour, minute, second),
+        _ => return None,
+    };
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    Some(days_since_epoch(year, month, day) * 86400 + hour * 3600 + minute * 60 + second)
+}
+
+/// days from 1970-01-01 to a date of the proleptic gregorian calendar
+fn days_since_epoch(year: u64, month: u64, day: u64) -> u64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+/// parses the `messages` array returned by `chatget`
+pub(crate) fn parse_messages(messages: serde_json::Value) -> Result<Vec<ChatMessage>, ChatError> {
+    if messages.is_null() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_value(messages).map_err(|e| ChatError::Other(e.to_string()))
+}
+
+/// keeps track of which messages were already yielded by a chat stream.
+/// the server always returns the whole channel so only the last snapshot has to be remembered.
+#[derive(Default)]
+pub(crate) struct ChatDedup {
+    seen: HashSet<ChatMessage>,
+}
+
+impl ChatDedup {
+    /// returns the messages of `snapshot` that were not in the previous snapshot, in server order
+    pub(crate) fn new_messages(&mut self, snapshot: Vec<ChatMessage>) -> Vec<ChatMessage> {
+        let fresh = snapshot
+            .iter()
+            .filter(|m| !self.seen.contains(*m))
+            .cloned()
+            .collect();
+        self.seen = snapshot.into_iter().collect();
+        fresh
+    }
+}
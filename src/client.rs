@@ -0,0 +1,192 @@
+/*!
+the api versions behind one trait
+
+every `KeyauthApi` implements [`KeyauthClient`], so launcher code can take a `Box<dyn KeyauthClient>` and the api version
+becomes a config option:
+```rust,ignore
+use keyauth::KeyauthClient;
+
+let mut auth: Box<dyn KeyauthClient> = match config.api_version.as_str() {
+    "1.1" => Box::new(keyauth::v1_1::KeyauthApi::new("application name", "ownerid", "application version", "https://keyauth.win/api/1.1/")),
+    _ => Box::new(keyauth::v1_2::KeyauthApi::new("application name", "ownerid", "application secret", "application version", "https://keyauth.win/api/1.2/")),
+};
+auth.init(None).await.inner().unwrap();
+auth.login("username".to_string(), "password".to_string(), None, None).await.inner().unwrap();
+```
+the inherent methods of each `KeyauthApi` still work without importing the trait, including the typed chat helpers
+(get_chat_messages, chat_stream). poll_presence only lives here.
+*/
+
+use crate::chat::{parse_messages, ChatDedup, ChatError, ChatMessage};
+use crate::events::Hook;
+use crate::integrity::self_hash;
+use crate::online::{OnlineUser, PresenceEvent, PresenceTracker};
+use crate::session::{AppInfo, UserSession};
+use crate::two_factor::{LoginError, TwoFactorSetup};
+use crate::Res;
+use async_trait::async_trait;
+use futures_util::stream::{self, BoxStream, StreamExt};
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::Duration;
+
+/// errors returned by try_log
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogError {
+    /// there was no answer keyauth can be trusted on (no connection, tampered response...), sending again can work
+    NotSent(String),
+    /// keyauth answered and didnt accept the message, contains the message returned by the server
+    Rejected(String),
+}
+
+impl fmt::Display for LogError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LogError::NotSent(msg) | LogError::Rejected(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for LogError {}
+
+/// the calls every api version supports, see the version modules for what each one does
+#[async_trait]
+pub trait KeyauthClient: Send + Sync {
+    /// initializes a session, has to run before any other call
+    async fn init(&mut self, hash: Option<&str>) -> Res<AppInfo>;
+    async fn register(
+        &mut self,
+        username: String,
+        password: String,
+        license: String,
+        hwid: Option<String>,
+    ) -> Res<UserSession>;
+    async fn upgrade(&self, username: String, license: String) -> Res<()>;
+    async fn login(
+        &mut self,
+        username: String,
+        password: String,
+        hwid: Option<String>,
+        code: Option<String>,
+    ) -> Res<UserSession, LoginError>;
+    async fn license(&mut self, license: String, hwid: Option<String>) -> Res<UserSession>;
+    async fn var(&self, varid: String) -> Res<String>;
+    async fn file(&self, fileid: String) -> Res<Vec<u8>>;
+    async fn webhook(&self, webid: String, params: String) -> Res<String>;
+    async fn checkblacklist(&mut self) -> Res<()>;
+    async fn check_session(&self) -> Res<bool>;
+    async fn fetch_online(&self) -> Res<serde_json::Value>;
+    async fn get_chat(&self, channel: String) -> Res<serde_json::Value>;
+    async fn send_chat_message(&self, channel: String, message: String) -> Res<(), ChatError>;
+    async fn ban(&self);
+    async fn setvar(&mut self, varname: String, varvalue: String) -> Res<()>;
+    async fn getvar(&self, varname: String) -> Res<String>;
+    async fn log(&self, message: String, pcuser: Option<String>);
+    async fn try_log(&self, message: String, pcuser: Option<String>) -> Res<(), LogError>;
+    async fn change_username(&mut self, new_username: String) -> Res<String>;
+    async fn enable_2fa(&self) -> Res<TwoFactorSetup>;
+    async fn confirm_2fa(&self, code: String) -> Res<()>;
+    async fn disable_2fa(&self, code: String) -> Res<()>;
+    async fn logout(&mut self) -> Res<()>;
+    async fn forgot_password(&self, username: String, email: String) -> Res<String>;
+    async fn fetch_stats(&mut self) -> Res<AppInfo>;
+
+    /// init with the md5 of the running executable as the hash, see crate::integrity
+    async fn init_with_self_hash(&mut self) -> Res<AppInfo> {
+        match self_hash() {
+            Ok(hash) => self.init(Some(&hash)).await,
+            Err(msg) => Res(Err(msg)),
+        }
+    }
+
+    /// registers a hook that is called with every AuthEvent, see crate::events
+    fn on_event(&mut self, hook: Hook);
+
+    /// session of the logged in user, None before login and after logout
+    fn user(&self) -> Option<&UserSession>;
+    /// username of the logged in user
+    fn username(&self) -> &str;
+    /// subscription of the logged in user
+    fn subscription(&self) -> &str;
+
+    /// same as fetch_online but returns the users typed, one per session. also updates the online user count to the
+    /// number of sessions, see crate::online
+    async fn fetch_online_users(&mut self) -> Res<Vec<OnlineUser>>;
+
+    /// creates a presence tracker starting from the online user count init returned
+    fn presence_tracker(&self) -> PresenceTracker;
+
+    /// fetches the online users and feeds them to the tracker, returns who joined and left since the last poll.
+    /// call it on an interval to track presence
+    async fn poll_presence(&mut self, tracker: &mut PresenceTracker) -> Res<Vec<PresenceEvent>> {
+        match self.fetch_online_users().await.inner() {
+            Ok(users) => Res(Ok(tracker.update(users))),
+            Err(msg) => Res(Err(msg)),
+        }
+    }
+
+    /// same as get_chat but returns the messages typed
+    async fn get_chat_messages(&self, channel: String) -> Res<Vec<ChatMessage>, ChatError> {
+        match self.get_chat(channel).await.inner() {
+            Ok(messages) => Res(parse_messages(messages)),
+            Err(msg) => Res(Err(ChatError::from(msg))),
+        }
+    }
+
+    /// polls a channel every `poll_interval` and yields every message that wasnt yielded by a previous poll.
+    /// the first poll yields the messages that are already in the channel.
+    /// errors are yielded too and the stream keeps polling, on ChatError::RateLimited it waits `retry_after`
+    /// (or twice as long as the last wait if the server didnt say) before polling again. drop the stream to stop polling
+    fn chat_stream(
+        &self,
+        channel: String,
+        poll_interval: Duration,
+    ) -> BoxStream<'_, Res<ChatMessage, ChatError>> {
+        const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+        struct State<'a, C: ?Sized> {
+            api: &'a C,
+            channel: String,
+            dedup: ChatDedup,
+            pending: VecDeque<ChatMessage>,
+            wait: Option<Duration>,
+        }
+
+        let state = State {
+            api: self,
+            channel,
+            dedup: ChatDedup::default(),
+            pending: VecDeque::new(),
+            wait: None,
+        };
+
+        stream::unfold(state, move |mut state| async move {
+            loop {
+                if let Some(msg) = state.pending.pop_front() {
+                    return Some((Res(Ok(msg)), state));
+                }
+                if let Some(wait) = state.wait {
+                    tokio::time::sleep(wait).await;
+                }
+                let last_wait = state.wait.unwrap_or(poll_interval);
+                state.wait = Some(poll_interval);
+                match state
+                    .api
+                    .get_chat_messages(state.channel.clone())
+                    .await
+                    .inner()
+                {
+                    Ok(messages) => state.pending.extend(state.dedup.new_messages(messages)),
+                    Err(err) => {
+                        if let ChatError::RateLimited { retry_after } = &err {
+                            let backoff = retry_after.unwrap_or((last_wait * 2).min(MAX_BACKOFF));
+                            state.wait = Some(backoff.max(poll_interval));
+                        }
+                        return Some((Res(Err(err)), state));
+                    }
+                }
+            }
+        })
+        .boxed()
+    }
+}
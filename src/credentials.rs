@@ -0,0 +1,123 @@
+/*!
+application credentials that are encrypted at compile time
+
+`KeyauthApi::new` takes the name, ownerid and secret as `&str`, so they end up verbatim in the binary.
+[`keyauth_app!`](crate::keyauth_app) encrypts them with goldberg at compile time instead, they are only decrypted into a
+temporary string while a request is built. plain credentials are shared by the clones of a client and wiped from memory
+when the last one is dropped. the values put in a request are wiped when reqwest is done with the body, except for the
+copies the http client makes while sending it:
+```rust,ignore
+let app = keyauth::keyauth_app! {
+    name: "application name",
+    owner_id: "ownerid",
+    secret: "application secret", // leave it out for 1.1 and 1.3
+    version: "1.0",
+};
+let mut auth = keyauth::v1_2::KeyauthApi::from_app(app, "https://keyauth.win/api/1.2/");
+```
+*/
+
+use std::fmt;
+use std::sync::Arc;
+use zeroize::Zeroizing;
+
+/// a credential that is either stored as is or decrypted every time it is used.
+/// clones share the stored value, it is zeroed when the last one is dropped
+#[derive(Clone)]
+pub struct Credential(Inner);
+
+#[derive(Clone)]
+enum Inner {
+    Plain(Arc<Zeroizing<String>>),
+    Encrypted(fn() -> String),
+}
+
+impl Credential {
+    pub fn plain(value: &str) -> Self {
+        Credential::from(value.to_string())
+    }
+
+    /// `decrypt` returns the value, used by keyauth_app!
+    #[doc(hidden)]
+    pub fn encrypted(decrypt: fn() -> String) -> Self {
+        Credential(Inner::Encrypted(decrypt))
+    }
+
+    /// the value in a string that is zeroed on drop
+    pub(crate) fn reveal(&self) -> Zeroizing<String> {
+        match &self.0 {
+            Inner::Plain(value) => Zeroizing::new(value.as_str().to_string()),
+            Inner::Encrypted(decrypt) => Zeroizing::new(decrypt()),
+        }
+    }
+}
+
+/// takes the string without copying it
+impl From<String> for Credential {
+    fn from(value: String) -> Self {
+        Credential(Inner::Plain(Arc::new(Zeroizing::new(value))))
+    }
+}
+
+impl Default for Credential {
+    fn default() -> Self {
+        Credential::plain("")
+    }
+}
+
+/// never shows the value, the request builders take it from `reveal`
+impl fmt::Debug for Credential {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Credential(..)")
+    }
+}
+
+/// what keyauth_app! expands to, pass it to `KeyauthApi::from_app`
+#[derive(Debug, Clone, Default)]
+pub struct AppCredentials {
+    pub name: Credential,
+    pub owner_id: Credential,
+    /// empty for apps declared without a secret
+    pub secret: Credential,
+    pub version: String,
+}
+
+/// declares the application credentials encrypted at compile time, see the credentials module
+#[macro_export]
+macro_rules! keyauth_app {
+    (
+        name: $name:literal,
+        owner_id: $owner_id:literal,
+        secret: $secret:literal,
+        version: $version:literal $(,)?
+    ) => {
+        $crate::credentials::AppCredentials {
+            name: $crate::credentials::Credential::encrypted(|| {
+                String::from($crate::__private::goldberg_string!($name))
+            }),
+            owner_id: $crate::credentials::Credential::encrypted(|| {
+                String::from($crate::__private::goldberg_string!($owner_id))
+            }),
+            secret: $crate::credentials::Credential::encrypted(|| {
+                String::from($crate::__private::goldberg_string!($secret))
+            }),
+            version: $version.to_string(),
+        }
+    };
+    (
+        name: $name:literal,
+        owner_id: $owner_id:literal,
+        version: $version:literal $(,)?
+    ) => {
+        $crate::credentials::AppCredentials {
+            name: $crate::credentials::Credential::encrypted(|| {
+                String::from($crate::__private::goldberg_string!($name))
+            }),
+            owner_id: $crate::credentials::Credential::encrypted(|| {
+                String::from($crate::__private::goldberg_string!($owner_id))
+            }),
+            secret: $crate::credentials::Credential::default(),
+            version: $version.to_string(),
+        }
+    };
+}
@@ -0,0 +1,74 @@
+/*!
+callbacks for the auth lifecycle
+
+hooks registered with `on_event` are called with every [`AuthEvent`] the client runs into, so the ui and telemetry can
+react in one place instead of checking the result of every call:
+```rust,ignore
+use keyauth::events::AuthEvent;
+
+auth.on_event(|event| match event {
+    AuthEvent::Tampered | AuthEvent::Blacklisted => std::process::exit(1),
+    AuthEvent::SubscriptionExpiring(sub) => println!("{} expires in {:?}", sub.subscription, sub.remaining()),
+    _ => {}
+});
+```
+hooks run on the task that made the call before it returns, so keep them short. clones of a client share its hooks.
+*/
+
+use crate::session::{AppInfo, Subscription, UserSession};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// something that happened to the session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthEvent {
+    /// init succeeded
+    Initialized(AppInfo),
+    /// login, register, license or web_login succeeded
+    LoggedIn(UserSession),
+    /// register, license or upgrade used a license key
+    LicenseActivated,
+    /// check_session found the session isnt valid anymore
+    SessionExpired,
+    /// checkblacklist found the hwid or ip blacklisted
+    Blacklisted,
+    /// a response failed the signature check
+    Tampered,
+    /// a subscription of the user that just logged in runs out within `expiry_warning`
+    SubscriptionExpiring(Subscription),
+}
+
+/// a registered callback
+pub type Hook = Arc<dyn Fn(&AuthEvent) + Send + Sync>;
+
+/// how long before a subscription runs out SubscriptionExpiring is sent, the default of `expiry_warning`
+pub const DEFAULT_EXPIRY_WARNING: Duration = Duration::from_secs(3 * 24 * 60 * 60);
+
+/// the hooks of a client
+#[derive(Clone, Default)]
+pub(crate) struct Hooks(Vec<Hook>);
+
+impl Hooks {
+    pub(crate) fn push(&mut self, hook: Hook) {
+        self.0.push(hook);
+    }
+
+    pub(crate) fn emit(&self, event: AuthEvent) {
+        for hook in &self.0 {
+            hook(&event);
+        }
+    }
+
+    /// LoggedIn and a SubscriptionExpiring for every subscription that runs out within `warning`
+    pub(crate) fn logged_in(&self, session: &UserSession, warning: Duration) {
+        if self.0.is_empty() {
+            return;
+        }
+        self.emit(AuthEvent::LoggedIn(session.clone()));
+        for sub in &session.subscriptions {
+            if sub.remaining() <= warning {
+                self.emit(AuthEvent::SubscriptionExpiring(sub.clone()));
+            }
+        }
+    }
+}
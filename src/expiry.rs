@@ -0,0 +1,270 @@
+/*!
+countdown to the end of the user's subscriptions
+
+[`ExpiryWatcher`] takes the subscriptions of a login/license response and counts down to their expiry with the system
+clock. the deadline is the server's `expiry`, or `timeleft` from now if that is earlier (or the only one sent), so a
+system clock that is behind when the watcher starts doesnt buy more time. subscriptions without either never expire and
+arent watched. `next` waits for the next [`ExpiryEvent`]: a warning when a subscription gets below one of the thresholds
+and an expiry when it runs out. before each of those the session is checked with `check_session`, if the server ended it
+the watcher says so and stops, if the server cant be reached it keeps counting.
+
+the system clock is compared with the monotonic one, at the start against the server's `timeleft` and then every
+`clock_check_interval`. when it was turned back ClockTampered is sent and the deadlines are moved back with it. the
+system clock getting ahead of the monotonic one is normal (the monotonic clock stops while the machine is suspended),
+it only moves the countdown along.
+
+`next` only borrows the client while it asks the server, a client shared with a
+[`KeyauthHandle`](crate::KeyauthHandle) is watched with `next_shared`, which only takes the read lock for the check.
+```rust,ignore
+let session = auth.login(username, password, None, None).await.inner().unwrap();
+let mut watcher = keyauth::expiry::ExpiryWatcher::new(&session, Default::default());
+while let Some(event) = watcher.next(&auth).await {
+    match event {
+        ExpiryEvent::Warning { subscription, threshold } => println!("{} ends in less than {:?}", subscription.subscription, threshold),
+        ExpiryEvent::Expired(_) | ExpiryEvent::SessionEnded | ExpiryEvent::ClockTampered { .. } => std::process::exit(0),
+    }
+}
+```
+*/
+
+use crate::session::{Subscription, UserSession};
+use crate::{KeyauthClient, KeyauthHandle, Res};
+use std::collections::VecDeque;
+use std::future::Future;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// what the watcher found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpiryEvent {
+    /// less than `threshold` is left on `subscription`, sent once per threshold
+    Warning {
+        subscription: Subscription,
+        threshold: Duration,
+    },
+    /// the countdown of the subscription reached zero
+    Expired(Subscription),
+    /// the system clock is `drift` off from the server's time or was turned back by `drift` while watching
+    ClockTampered { drift: Duration },
+    /// check_session said the session isnt valid anymore, nothing is watched after this
+    SessionEnded,
+}
+
+/// when the watcher sends events
+#[derive(Debug, Clone)]
+pub struct ExpiryConfig {
+    /// a warning is sent when a subscription gets below each of these
+    pub thresholds: Vec<Duration>,
+    /// how far the system clock can be off before ClockTampered is sent
+    pub max_clock_drift: Duration,
+    /// how often the system clock is compared with the monotonic one while waiting
+    pub clock_check_interval: Duration,
+}
+
+impl Default for ExpiryConfig {
+    fn default() -> Self {
+        Self {
+            thresholds: vec![
+                Duration::from_secs(24 * 60 * 60),
+                Duration::from_secs(60 * 60),
+            ],
+            max_clock_drift: Duration::from_secs(5 * 60),
+            clock_check_interval: Duration::from_secs(60),
+        }
+    }
+}
+
+/// counts down the subscriptions of a session, see the module docs
+#[derive(Debug, Clone)]
+pub struct ExpiryWatcher {
+    subs: Vec<Watched>,
+    /// longest first
+    thresholds: Vec<Duration>,
+    config: ExpiryConfig,
+    pending: VecDeque<ExpiryEvent>,
+    /// the monotonic and system clock at the same moment, only used to notice the system clock being turned back
+    baseline: (Instant, SystemTime),
+}
+
+#[derive(Debug, Clone)]
+struct Watched {
+    sub: Subscription,
+    /// by the system clock
+    deadline: SystemTime,
+    /// thresholds already warned about
+    warned: usize,
+}
+
+impl ExpiryWatcher {
+    pub fn new(session: &UserSession, config: ExpiryConfig) -> Self {
+        let wall = SystemTime::now();
+        let mut thresholds = config.thresholds.clone();
+        thresholds.sort_unstable_by(|a, b| b.cmp(a));
+        thresholds.dedup();
+
+        let mut pending = VecDeque::new();
+        let mut drift = Duration::ZERO;
+        let subs = session
+            .subscriptions
+            .iter()
+            .filter_map(|sub| {
+                let by_expiry =
+                    (sub.expiry > 0).then(|| UNIX_EPOCH + Duration::from_secs(sub.expiry));
+                let by_timeleft =
+                    (sub.timeleft > 0).then(|| wall + Duration::from_secs(sub.timeleft));
+                let deadline = match (by_expiry, by_timeleft) {
+                    (Some(expiry), Some(timeleft)) => {
+                        // the system clock should say the same as the server
+                        drift = drift.max(between(expiry, timeleft));
+                        expiry.min(timeleft)
+                    }
+                    (Some(deadline), None) | (None, Some(deadline)) => deadline,
+                    (None, None) => return None,
+                };
+                Some(Watched {
+                    sub: sub.clone(),
+                    deadline,
+                    warned: 0,
+                })
+            })
+            .collect();
+        if drift > config.max_clock_drift {
+            pending.push_back(ExpiryEvent::ClockTampered { drift });
+        }
+
+        Self {
+            subs,
+            thresholds,
+            config,
+            pending,
+            baseline: (Instant::now(), wall),
+        }
+    }
+
+    /// waits for the next event, None once every subscription expired or the session ended
+    pub async fn next<C: KeyauthClient + Sync + ?Sized>(
+        &mut self,
+        auth: &C,
+    ) -> Option<ExpiryEvent> {
+        self.next_checked(|| auth.check_session()).await
+    }
+
+    /// same as next for a client behind a KeyauthHandle, the lock is only held while the session is checked
+    pub async fn next_shared<C: KeyauthClient>(
+        &mut self,
+        auth: &KeyauthHandle<C>,
+    ) -> Option<ExpiryEvent> {
+        self.next_checked(|| auth.check_session()).await
+    }
+
+    async fn next_checked<F, Fut>(&mut self, mut check_session: F) -> Option<ExpiryEvent>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Res<bool>>,
+    {
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Some(event);
+            }
+            if self.subs.is_empty() {
+                return None;
+            }
+            if let Some(drift) = self.clock_turned_back() {
+                return Some(ExpiryEvent::ClockTampered { drift });
+            }
+
+            let now = SystemTime::now();
+            let next = self
+                .subs
+                .iter()
+                .map(|watched| self.next_moment(watched))
+                .min()
+                .unwrap_or(now);
+            if let Ok(wait) = next.duration_since(now) {
+                if !wait.is_zero() {
+                    tokio::time::sleep(wait.min(self.config.clock_check_interval)).await;
+                    continue;
+                }
+            }
+
+            // an error means the server couldnt be asked, the countdown goes on without it
+            if let Ok(false) = check_session().await.inner() {
+                self.subs.clear();
+                return Some(ExpiryEvent::SessionEnded);
+            }
+            self.collect_due(SystemTime::now());
+        }
+    }
+
+    /// the subscriptions that are still counting down
+    pub fn subscriptions(&self) -> Vec<&Subscription> {
+        self.subs.iter().map(|watched| &watched.sub).collect()
+    }
+
+    /// when the next warning or the expiry of `watched` is due
+    fn next_moment(&self, watched: &Watched) -> SystemTime {
+        match self.thresholds.get(watched.warned) {
+            Some(threshold) => watched
+                .deadline
+                .checked_sub(*threshold)
+                .unwrap_or(UNIX_EPOCH),
+            None => watched.deadline,
+        }
+    }
+
+    /// queues the events that are due at `now` and drops the expired subscriptions
+    fn collect_due(&mut self, now: SystemTime) {
+        let thresholds = &self.thresholds;
+        let pending = &mut self.pending;
+        self.subs.retain_mut(|watched| {
+            let left = watched.deadline.duration_since(now).unwrap_or_default();
+            if left.is_zero() {
+                pending.push_back(ExpiryEvent::Expired(watched.sub.clone()));
+                return false;
+            }
+            // thresholds that were passed together only get one warning, for the shortest
+            let mut passed = None;
+            while let Some(threshold) = thresholds.get(watched.warned) {
+                if left > *threshold {
+                    break;
+                }
+                passed = Some(*threshold);
+                watched.warned += 1;
+            }
+            if let Some(threshold) = passed {
+                pending.push_back(ExpiryEvent::Warning {
+                    subscription: watched.sub.clone(),
+                    threshold,
+                });
+            }
+            true
+        });
+    }
+
+    /// how far the system clock was turned back since the last check, if its more than allowed. the deadlines are moved
+    /// back by as much so the countdown isnt extended. a system clock that got ahead (suspend, ntp) is taken as is
+    fn clock_turned_back(&mut self) -> Option<Duration> {
+        let (instant, wall) = self.baseline;
+        let expected = wall + instant.elapsed();
+        let now = SystemTime::now();
+        let drift = between(expected, now);
+        if drift <= self.config.max_clock_drift {
+            return None;
+        }
+        self.baseline = (Instant::now(), now);
+        if now > expected {
+            return None;
+        }
+        for watched in &mut self.subs {
+            watched.deadline = watched.deadline.checked_sub(drift).unwrap_or(UNIX_EPOCH);
+        }
+        Some(drift)
+    }
+}
+
+/// the time between two moments of the system clock
+fn between(a: SystemTime, b: SystemTime) -> Duration {
+    match a.duration_since(b) {
+        Ok(after) => after,
+        Err(before) => before.duration(),
+    }
+}
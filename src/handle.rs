@@ -0,0 +1,198 @@
+/*!
+a cloneable handle to share one client between tasks
+
+the `KeyauthApi` calls that change the session or user state (init, login, logout...) take `&mut self`, so sharing one
+between tokio tasks means a lock around the client. [`KeyauthHandle`] keeps it behind an `Arc<RwLock>`: the calls that
+change state take the write lock, everything else only reads the client and shares the read lock, so var/file/webhook
+calls from different tasks go out at the same time.
+```rust,ignore
+let auth = keyauth::KeyauthHandle::new(keyauth::v1_2::KeyauthApi::new("application name", "ownerid", "application secret", "application version", "api url"));
+auth.init(None).await.inner().unwrap();
+auth.login("username".to_string(), "password".to_string(), None, None).await.inner().unwrap();
+
+let worker = auth.clone();
+tokio::spawn(async move { worker.var("varid".to_string()).await });
+println!("logged in as {}", auth.read().await.username);
+```
+*/
+
+use crate::chat::{ChatError, ChatMessage};
+use crate::client::LogError;
+use crate::events::AuthEvent;
+use crate::online::OnlineUser;
+use crate::session::{AppInfo, UserSession};
+use crate::two_factor::{LoginError, TwoFactorSetup};
+use crate::{KeyauthClient, Res};
+use std::sync::Arc;
+use tokio::sync::{RwLock, RwLockReadGuard};
+
+/// shared client, clones point to the same client
+pub struct KeyauthHandle<C> {
+    inner: Arc<RwLock<C>>,
+}
+
+impl<C> Clone for KeyauthHandle<C> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<C: KeyauthClient> KeyauthHandle<C> {
+    pub fn new(client: C) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(client)),
+        }
+    }
+
+    /// read access to the client, for its public fields. dont hold it across a call on the handle that changes state
+    pub async fn read(&self) -> RwLockReadGuard<'_, C> {
+        self.inner.read().await
+    }
+
+    /// registers a hook on the shared client, see crate::events
+    pub async fn on_event<F: Fn(&AuthEvent) + Send + Sync + 'static>(&self, hook: F) {
+        self.inner.write().await.on_event(Arc::new(hook))
+    }
+
+    pub async fn init(&self, hash: Option<&str>) -> Res<AppInfo> {
+        self.inner.write().await.init(hash).await
+    }
+
+    pub async fn init_with_self_hash(&self) -> Res<AppInfo> {
+        self.inner.write().await.init_with_self_hash().await
+    }
+
+    pub async fn register(
+        &self,
+        username: String,
+        password: String,
+        license: String,
+        hwid: Option<String>,
+    ) -> Res<UserSession> {
+        self.inner
+            .write()
+            .await
+            .register(username, password, license, hwid)
+            .await
+    }
+
+    pub async fn upgrade(&self, username: String, license: String) -> Res<()> {
+        self.inner.read().await.upgrade(username, license).await
+    }
+
+    pub async fn login(
+        &self,
+        username: String,
+        password: String,
+        hwid: Option<String>,
+        code: Option<String>,
+    ) -> Res<UserSession, LoginError> {
+        self.inner
+            .write()
+            .await
+            .login(username, password, hwid, code)
+            .await
+    }
+
+    pub async fn license(&self, license: String, hwid: Option<String>) -> Res<UserSession> {
+        self.inner.write().await.license(license, hwid).await
+    }
+
+    pub async fn logout(&self) -> Res<()> {
+        self.inner.write().await.logout().await
+    }
+
+    pub async fn change_username(&self, new_username: String) -> Res<String> {
+        self.inner.write().await.change_username(new_username).await
+    }
+
+    pub async fn fetch_stats(&self) -> Res<AppInfo> {
+        self.inner.write().await.fetch_stats().await
+    }
+
+    pub async fn fetch_online_users(&self) -> Res<Vec<OnlineUser>> {
+        self.inner.write().await.fetch_online_users().await
+    }
+
+    pub async fn var(&self, varid: String) -> Res<String> {
+        self.inner.read().await.var(varid).await
+    }
+
+    pub async fn file(&self, fileid: String) -> Res<Vec<u8>> {
+        self.inner.read().await.file(fileid).await
+    }
+
+    pub async fn webhook(&self, webid: String, params: String) -> Res<String> {
+        self.inner.read().await.webhook(webid, params).await
+    }
+
+    pub async fn checkblacklist(&self) -> Res<()> {
+        self.inner.write().await.checkblacklist().await
+    }
+
+    pub async fn check_session(&self) -> Res<bool> {
+        self.inner.read().await.check_session().await
+    }
+
+    pub async fn fetch_online(&self) -> Res<serde_json::Value> {
+        self.inner.read().await.fetch_online().await
+    }
+
+    pub async fn get_chat(&self, channel: String) -> Res<serde_json::Value> {
+        self.inner.read().await.get_chat(channel).await
+    }
+
+    pub async fn get_chat_messages(&self, channel: String) -> Res<Vec<ChatMessage>, ChatError> {
+        self.inner.read().await.get_chat_messages(channel).await
+    }
+
+    pub async fn send_chat_message(&self, channel: String, message: String) -> Res<(), ChatError> {
+        self.inner
+            .read()
+            .await
+            .send_chat_message(channel, message)
+            .await
+    }
+
+    pub async fn ban(&self) {
+        self.inner.read().await.ban().await
+    }
+
+    pub async fn setvar(&self, varname: String, varvalue: String) -> Res<()> {
+        self.inner.write().await.setvar(varname, varvalue).await
+    }
+
+    pub async fn getvar(&self, varname: String) -> Res<String> {
+        self.inner.read().await.getvar(varname).await
+    }
+
+    pub async fn log(&self, message: String, pcuser: Option<String>) {
+        self.inner.read().await.log(message, pcuser).await
+    }
+
+    pub async fn try_log(&self, message: String, pcuser: Option<String>) -> Res<(), LogError> {
+        self.inner.read().await.try_log(message, pcuser).await
+    }
+
+    pub async fn forgot_password(&self, username: String, email: String) -> Res<String> {
+        self.inner
+            .read()
+            .await
+            .forgot_password(username, email)
+            .await
+    }
+
+    pub async fn enable_2fa(&self) -> Res<TwoFactorSetup> {
+        self.inner.read().await.enable_2fa().await
+    }
+
+    pub async fn confirm_2fa(&self, code: String) -> Res<()> {
+        self.inner.read().await.confirm_2fa(code).await
+    }
+
+    pub async fn disable_2fa(&self, code: String) -> Res<()> {
+        self.inner.read().await.disable_2fa(code).await
+    }
+}
@@ -0,0 +1,33 @@
+/*!
+hash of the running executable for keyauth's hash check
+
+with hash checking enabled keyauth compares the hash sent on init against the hashes registered for the application,
+the hash is the md5 of the executable file. use `KeyauthClient::init_with_self_hash` to send it, and register a new build
+with the seller api (`SellerApi::add_hash`), `cargo run --example hash -- path/to/binary` prints it and with
+`--features seller` and a seller key after the path it registers it too.
+*/
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+/// md5 of the running executable as lowercase hex, on linux this reads /proc/self/exe
+pub fn self_hash() -> Result<String, String> {
+    let exe = std::env::current_exe().map_err(|e| e.to_string())?;
+    file_hash(exe)
+}
+
+/// md5 of the file at `path` as lowercase hex, the same hash self_hash gives when that file is running
+pub fn file_hash(path: impl AsRef<Path>) -> Result<String, String> {
+    let mut file = File::open(path).map_err(|e| e.to_string())?;
+    let mut context = md5::Context::new();
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).map_err(|e| e.to_string())?;
+        if n == 0 {
+            break;
+        }
+        context.consume(&buf[..n]);
+    }
+    Ok(format!("{:x}", context.compute()))
+}
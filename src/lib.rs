@@ -6,22 +6,102 @@ example
 keyauth = { version = "*" } # this will enable 1.2 api version (default)
 ```
 by default the 1.2 api is enabled because it is most commonly used. so if you dont want the 1.2 api you have to disable it.
+the 1.3 api (`v1_3` feature) verifies responses with keyauth's ed25519 public key, so it doesnt need the application secret.
+the legacy 1.0 (`v1_0`, aes encrypted) and 1.1 (`v1_1`, unsigned) apis are only there for applications that havent moved yet.
 ```toml
 keyauth = { version = "*", features = ["v1_1", "seller"], default-features = false } # this will enable 1.1 and seller api
 ```
 the ``default-features = false`` disabled the default v1_2 api.
+the `full` feature enables every api version and the optional modules (seller, logger, log and tracing).
 
 basic usage:
-```rust
+```rust,ignore
 let mut auth = keyauth::v1_2::KeyauthApi::new("application name", "ownerid", "application secret", "application version", "api url"); // if you dont have a custom domain for api use "https://keyauth.win/api/1.2/"
-auth.init(None).unwrap(); // None -> no hash set, Some("hash") -> if you have has checking enabled
-auth.login("username".to_string(), "password".to_string(), Some("hwid".to_string())).unwrap(); // if you want to automaticly generate hwid use None insted of Some(...)
+auth.init(None).await.inner().unwrap(); // None -> no hash set, Some("hash") -> if you have has checking enabled
+auth.login("username".to_string(), "password".to_string(), Some("hwid".to_string()), None).await.inner().unwrap(); // if you want to automaticly generate hwid use None insted of Some(...), the last argument is the 2fa code
 ```
 
 also if you want to use an obfuscator for rust i recommend using [obfstr](https://crates.io/crates/obfstr) and [llvm obfuscator](https://github.com/eshard/obfuscator-llvm/wiki/Rust-obfuscation-guide)
 
+with the `tracing` feature every request gets a `keyauth_request` span (type, latency, result, the parameters with the secrets
+redacted), add a [tracing](https://docs.rs/tracing) subscriber to see them.
+
+the 1.2 api can keep working without a connection for a while after a login, see the [offline](crate::offline) module.
+
 if the panic feature is enabled then the v1_2 api will panic insted of returning an error when it detects that the request was tampered with
 */
+// the shared helpers are only used by the api version modules
+#![cfg_attr(
+    not(any(feature = "v1_0", feature = "v1_1", feature = "v1_2", feature = "v1_3")),
+    allow(dead_code)
+)]
+
+/// kills the process if a debugger is attached, only in release builds on linux
+#[allow(unused_macros)]
+macro_rules! nodebug {
+    () => {
+        #[cfg(target_os = "linux")]
+        #[cfg(not(debug_assertions))]
+        debugoff::multi_ptraceme_or_die();
+    };
+}
 
+#[cfg(any(feature = "v1_0", feature = "v1_1", feature = "v1_2", feature = "v1_3"))]
+mod api;
+pub mod chat;
+pub mod client;
+pub mod credentials;
+pub mod events;
+pub mod expiry;
+pub mod handle;
+pub mod integrity;
+#[cfg(feature = "logger")]
+pub mod logger;
+#[cfg(feature = "v1_2")]
+pub mod offline;
+pub mod online;
+#[cfg(feature = "seller")]
+pub mod seller;
+pub mod session;
+mod trace;
+mod transport;
+pub mod two_factor;
+#[cfg(feature = "v1_0")]
+pub mod v1_0;
+#[cfg(feature = "v1_1")]
+pub mod v1_1;
 #[cfg(feature = "v1_2")]
 pub mod v1_2;
+#[cfg(feature = "v1_3")]
+pub mod v1_3;
+#[cfg(feature = "web_loader")]
+pub mod web_loader;
+
+pub use client::KeyauthClient;
+pub use handle::KeyauthHandle;
+
+#[doc(hidden)]
+pub mod __private {
+    pub use goldberg::goldberg_string;
+}
+
+/// result returned by every api call, use inner to get the Result
+pub struct Res<T, E = String>(pub(crate) Result<T, E>);
+
+impl Default for Res<()> {
+    fn default() -> Self {
+        Res(Err("Default Error".to_string()))
+    }
+}
+
+impl<T, E> Res<T, E> {
+    pub fn inner(self) -> Result<T, E> {
+        self.0
+    }
+}
+
+impl<T: Clone, E: Clone> Res<T, E> {
+    pub fn clone_inner(&self) -> Result<T, E> {
+        self.0.clone()
+    }
+}
@@ -0,0 +1,304 @@
+/*!
+queued logging to keyauth
+
+`log` sends a request per message and waits for it. [`RemoteLogger`] puts the messages in a queue instead and a
+background task sends them, at most `max_per_window` every `window`. when a message cant be sent (no connection...)
+the queue is written to `queue_file` and sending is retried after `retry_after`. a logger started with the same
+`queue_file` later picks the messages up again, so logs from an offline run are sent on the next one. a message keyauth
+answers with an error (no session, logging disabled...) would be rejected again, so it is dropped and counted in
+`rejected`. start the logger after init so its messages arent rejected for the missing session.
+```rust,ignore
+let auth = keyauth::KeyauthHandle::new(keyauth::v1_2::KeyauthApi::new("application name", "ownerid", "application secret", "application version", "api url"));
+let logger = keyauth::logger::RemoteLogger::start(auth.clone(), keyauth::logger::LoggerConfig {
+    queue_file: Some("keyauth-logs.json".into()),
+    ..Default::default()
+});
+logger.log("launcher started");
+// ...
+logger.shutdown().await; // stops sending and writes what wasnt sent to the queue file
+```
+with the `log` feature it can also be installed as the [`log`](https://docs.rs/log) logger with `logger.install()`,
+records at `level` or above are sent (except the ones from the http stack the logger itself uses).
+*/
+
+use crate::client::LogError;
+use crate::{KeyauthClient, KeyauthHandle};
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+use tokio::task::JoinHandle;
+
+/// how the logger sends and keeps messages
+#[derive(Debug, Clone)]
+pub struct LoggerConfig {
+    /// most messages sent per window
+    pub max_per_window: usize,
+    pub window: Duration,
+    /// wait after a message couldnt be sent
+    pub retry_after: Duration,
+    /// the oldest messages are dropped when more are queued
+    pub max_queued: usize,
+    /// where unsent messages are kept, None keeps them in memory only
+    pub queue_file: Option<PathBuf>,
+    /// sent as pcuser with every message, None sends the logged in username
+    pub pcuser: Option<String>,
+    /// records below this arent sent
+    #[cfg(feature = "log")]
+    pub level: log::LevelFilter,
+}
+
+impl Default for LoggerConfig {
+    fn default() -> Self {
+        Self {
+            max_per_window: 5,
+            window: Duration::from_secs(10),
+            retry_after: Duration::from_secs(30),
+            max_queued: 1000,
+            queue_file: None,
+            pcuser: None,
+            #[cfg(feature = "log")]
+            level: log::LevelFilter::Warn,
+        }
+    }
+}
+
+/// queues messages and sends them from a background task, clones share the queue
+#[derive(Clone)]
+pub struct RemoteLogger {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    /// messages with an id, so the task only removes the one it sent even if the front was dropped meanwhile
+    queue: Mutex<VecDeque<(u64, String)>>,
+    next_id: AtomicU64,
+    rejected: AtomicUsize,
+    notify: Notify,
+    /// only wakes the retry wait, new messages dont make it retry sooner
+    shutdown: Notify,
+    closed: AtomicBool,
+    task: Mutex<Option<JoinHandle<()>>>,
+    config: LoggerConfig,
+}
+
+impl RemoteLogger {
+    /// starts sending, the background task first puts the messages of the queue file (if there is one) in front of the
+    /// queue. has to be called inside a tokio runtime
+    pub fn start<C>(auth: KeyauthHandle<C>, config: LoggerConfig) -> Self
+    where
+        C: KeyauthClient + 'static,
+    {
+        let shared = Arc::new(Shared {
+            queue: Mutex::new(VecDeque::new()),
+            next_id: AtomicU64::new(0),
+            rejected: AtomicUsize::new(0),
+            notify: Notify::new(),
+            shutdown: Notify::new(),
+            closed: AtomicBool::new(false),
+            task: Mutex::new(None),
+            config,
+        });
+        let task = tokio::spawn(run(auth, shared.clone()));
+        *shared.task.lock().unwrap() = Some(task);
+        Self { shared }
+    }
+
+    /// queues a message, returns right away. ignored after shutdown
+    pub fn log(&self, message: impl Into<String>) {
+        if !self.shared.closed.load(Ordering::SeqCst) {
+            self.shared.push(message.into());
+        }
+    }
+
+    /// number of messages that havent been sent yet
+    pub fn pending(&self) -> usize {
+        self.shared.queue.lock().unwrap().len()
+    }
+
+    /// number of messages keyauth didnt accept, they arent sent again
+    pub fn rejected(&self) -> usize {
+        self.shared.rejected.load(Ordering::SeqCst)
+    }
+
+    /// stops the background task and writes the unsent messages to the queue file
+    pub async fn shutdown(&self) {
+        self.shared.closed.store(true, Ordering::SeqCst);
+        self.shared.notify.notify_one();
+        self.shared.shutdown.notify_one();
+        let task = self.shared.task.lock().unwrap().take();
+        if let Some(task) = task {
+            let _ = task.await;
+        }
+    }
+
+    /// sets this logger as the `log` crate logger and the max level to `level`
+    #[cfg(feature = "log")]
+    pub fn install(&self) -> Result<(), log::SetLoggerError> {
+        log::set_boxed_logger(Box::new(self.clone()))?;
+        log::set_max_level(self.shared.config.level);
+        Ok(())
+    }
+}
+
+impl Shared {
+    fn push(&self, message: String) {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let mut queue = self.queue.lock().unwrap();
+        queue.push_back((id, message));
+        while queue.len() > self.config.max_queued {
+            queue.pop_front();
+        }
+        drop(queue);
+        self.notify.notify_one();
+    }
+
+    /// puts the messages of the queue file before the ones that were logged since start
+    async fn load(&self) {
+        let path = match &self.config.queue_file {
+            Some(path) => path,
+            None => return,
+        };
+        let queued: Vec<String> = match tokio::fs::read(path).await {
+            Ok(bytes) => serde_json::from_slice(&bytes).unwrap_or_default(),
+            Err(_) => return,
+        };
+        let mut queue = self.queue.lock().unwrap();
+        for message in queued.into_iter().rev() {
+            let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+            queue.push_front((id, message));
+        }
+        while queue.len() > self.config.max_queued {
+            queue.pop_front();
+        }
+    }
+
+    /// writes the queue to the queue file, an empty queue removes the file
+    async fn save(&self) {
+        let path = match &self.config.queue_file {
+            Some(path) => path,
+            None => return,
+        };
+        let messages: Vec<String> = self
+            .queue
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(_, message)| message.clone())
+            .collect();
+        if messages.is_empty() {
+            let _ = tokio::fs::remove_file(path).await;
+        } else if let Ok(json) = serde_json::to_vec(&messages) {
+            let _ = tokio::fs::write(path, json).await;
+        }
+    }
+
+    /// sleeps for `duration` or until something is queued or the logger is shut down
+    async fn wait(&self, duration: Duration) {
+        let _ = tokio::time::timeout(duration, self.notify.notified()).await;
+    }
+}
+
+async fn run<C>(auth: KeyauthHandle<C>, shared: Arc<Shared>)
+where
+    C: KeyauthClient + 'static,
+{
+    let window = shared.config.window;
+    let mut sent: VecDeque<Instant> = VecDeque::new();
+    shared.load().await;
+    // the file has to be kept up to date once something was written to it
+    let mut saved = match &shared.config.queue_file {
+        Some(path) => tokio::fs::try_exists(path).await.unwrap_or(false),
+        None => false,
+    };
+
+    while !shared.closed.load(Ordering::SeqCst) {
+        let front = shared.queue.lock().unwrap().front().cloned();
+        let (id, message) = match front {
+            Some(front) => front,
+            None => {
+                shared.notify.notified().await;
+                continue;
+            }
+        };
+
+        while sent.front().is_some_and(|at| at.elapsed() >= window) {
+            sent.pop_front();
+        }
+        if sent.len() >= shared.config.max_per_window.max(1) {
+            shared.wait(window.saturating_sub(sent[0].elapsed())).await;
+            continue;
+        }
+
+        match auth
+            .try_log(message, shared.config.pcuser.clone())
+            .await
+            .inner()
+        {
+            Err(LogError::NotSent(_)) => {
+                shared.save().await;
+                saved = true;
+                tokio::select! {
+                    _ = tokio::time::sleep(shared.config.retry_after) => {}
+                    _ = shared.shutdown.notified() => {}
+                }
+            }
+            result => {
+                sent.push_back(Instant::now());
+                if result.is_err() {
+                    shared.rejected.fetch_add(1, Ordering::SeqCst);
+                }
+                {
+                    let mut queue = shared.queue.lock().unwrap();
+                    if queue.front().map(|(front, _)| *front) == Some(id) {
+                        queue.pop_front();
+                    }
+                }
+                if saved {
+                    shared.save().await;
+                    saved = !shared.queue.lock().unwrap().is_empty();
+                }
+            }
+        }
+    }
+    shared.save().await;
+}
+
+#[cfg(feature = "log")]
+impl log::Log for RemoteLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        // sending a message logs through these, forwarding them would never stop
+        const OWN_TARGETS: [&str; 8] = [
+            "keyauth_obf",
+            "reqwest",
+            "hyper",
+            "h2",
+            "want",
+            "mio",
+            "tokio",
+            "rustls",
+        ];
+        metadata.level() <= self.shared.config.level
+            && !OWN_TARGETS
+                .iter()
+                .any(|target| metadata.target().starts_with(target))
+    }
+
+    fn log(&self, record: &log::Record) {
+        if self.enabled(record.metadata()) {
+            RemoteLogger::log(
+                self,
+                format!(
+                    "[{}] {}: {}",
+                    record.level(),
+                    record.target(),
+                    record.args()
+                ),
+            );
+        }
+    }
+
+    fn flush(&self) {}
+}
@@ -0,0 +1,147 @@
+/*!
+offline grace mode for the 1.2 api
+
+with `with_offline`, every successful `login`/`license` saves the response to `snapshot_file`, exactly as keyauth sent
+it and with its hmac signature. when keyauth cant be reached later, `offline_login` loads the snapshot, checks the
+signature again and logs in from it, as long as it is younger than `grace_period` and was made with the same hwid.
+the hwid and the save time are sealed with a key derived from the application secret, so a snapshot cant be moved to
+another machine or made younger by editing the file. the username or license key isnt saved, only an hmac of it that
+`offline_login` checks the one it is given against, and on unix the file is only readable by its owner. a snapshot without a subscription that has time left isnt accepted.
+an online login that keyauth rejects for good (banned, invalid or expired key) removes the snapshot, network errors
+and 2fa prompts keep it.
+```rust,ignore
+let mut auth = auth.with_offline(keyauth::offline::OfflineConfig::new("keyauth-session.json"));
+let session = match auth.init(None).await.inner() {
+    Ok(_) => auth.license(key.clone(), None).await.inner(),
+    Err(_) => auth.offline_login(key, None).inner(), // no connection, use the last login
+};
+```
+the grace period is counted with the system clock, a clock set before the save time makes the snapshot invalid.
+*/
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::io::AsyncWriteExt;
+use zeroize::{Zeroize, Zeroizing};
+
+/// where the snapshot is kept and how long it can be used
+#[derive(Debug, Clone)]
+pub struct OfflineConfig {
+    pub snapshot_file: PathBuf,
+    /// how long after the last online login the snapshot is accepted
+    pub grace_period: Duration,
+}
+
+impl OfflineConfig {
+    /// snapshot in `snapshot_file` with a grace period of 3 days
+    pub fn new(snapshot_file: impl Into<PathBuf>) -> Self {
+        Self {
+            snapshot_file: snapshot_file.into(),
+            grace_period: Duration::from_secs(3 * 24 * 60 * 60),
+        }
+    }
+}
+
+/// a verified login response and what is needed to verify it again
+#[derive(Serialize, Deserialize)]
+pub(crate) struct Snapshot {
+    /// the response body as keyauth sent it
+    pub(crate) body: String,
+    /// the signature header of the response
+    pub(crate) signature: String,
+    /// the session key the response was signed with
+    pub(crate) enckey: String,
+    pub(crate) hwid: String,
+    /// hmac of the username or license key that logged in, the key itself isnt written to disk
+    pub(crate) login: String,
+    /// unix time in seconds
+    pub(crate) saved_at: u64,
+    /// hmac of everything above
+    #[serde(default)]
+    pub(crate) seal: String,
+}
+
+impl Snapshot {
+    pub(crate) fn new(body: &str, signature: &str, enckey: &str, hwid: &str, login: &str) -> Self {
+        Self {
+            body: body.to_string(),
+            signature: signature.to_string(),
+            enckey: enckey.to_string(),
+            hwid: hwid.to_string(),
+            login: login.to_string(),
+            saved_at: unix_secs(),
+            seal: String::new(),
+        }
+    }
+
+    pub(crate) fn load(path: &Path) -> Result<Self, String> {
+        let bytes = std::fs::read(path).map_err(|_| "no offline snapshot".to_string())?;
+        serde_json::from_slice(&bytes).map_err(|_| "offline snapshot is corrupted".to_string())
+    }
+
+    /// writes the snapshot, on unix a new file is created with mode 0600
+    pub(crate) async fn save(&self, path: &Path) -> Result<(), String> {
+        let json = Zeroizing::new(serde_json::to_vec(self).map_err(|e| e.to_string())?);
+        let mut options = tokio::fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = options.open(path).await.map_err(|e| e.to_string())?;
+        file.write_all(&json).await.map_err(|e| e.to_string())?;
+        // tokio finishes the write in the background otherwise
+        file.flush().await.map_err(|e| e.to_string())
+    }
+
+    /// what the seal is made of, a json array so the fields cant be shifted into each other
+    pub(crate) fn sealed(&self) -> String {
+        serde_json::json!([
+            self.body,
+            self.signature,
+            self.enckey,
+            self.hwid,
+            self.login,
+            self.saved_at
+        ])
+        .to_string()
+    }
+
+    /// time since the snapshot was saved, None if the clock is before the save time
+    pub(crate) fn age(&self) -> Option<Duration> {
+        unix_secs()
+            .checked_sub(self.saved_at)
+            .map(Duration::from_secs)
+    }
+}
+
+impl Drop for Snapshot {
+    fn drop(&mut self) {
+        self.body.zeroize();
+        self.enckey.zeroize();
+        self.login.zeroize();
+    }
+}
+
+/// keyauth rejected the login for good, the snapshot shouldnt outlive it.
+/// a wrong password, a 2fa prompt or a hwid mismatch doesnt count
+pub(crate) fn revokes_snapshot(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    [
+        "banned",
+        "blacklisted",
+        "expired",
+        "no active subscription",
+        "invalid license",
+        "invalid key",
+        "key not found",
+    ]
+    .iter()
+    .any(|reason| lower.contains(reason))
+}
+
+fn unix_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since| since.as_secs())
+        .unwrap_or(0)
+}
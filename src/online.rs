@@ -0,0 +1,98 @@
+/*!
+typed online users and a presence tracker
+
+the raw `fetchOnline` response is an array of `{ credential }` objects, one per session, so a user logged in twice is in
+it twice. the online count is the number of sessions everywhere (init, fetch_online_users, the tracker), like keyauth's
+`numOnlineUsers`. [`PresenceTracker`] diffs two snapshots and tells you which users joined and left, a user with a
+second session doesnt join again and only leaves when their last session ends.
+*/
+
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// a user that currently has an active session
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+pub struct OnlineUser {
+    /// username or license key the session was authenticated with
+    pub credential: String,
+}
+
+/// change between two snapshots of the online users
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PresenceEvent {
+    Joined(OnlineUser),
+    Left(OnlineUser),
+}
+
+/// parses the `users` array returned by `fetchOnline`
+pub(crate) fn parse_users(users: serde_json::Value) -> Result<Vec<OnlineUser>, String> {
+    if users.is_null() {
+        return Ok(Vec::new());
+    }
+    serde_json::from_value(users).map_err(|e| e.to_string())
+}
+
+/// keeps the last snapshot of online users and emits join/leave events when a new one comes in.
+/// until the first snapshot the count is the `numOnlineUsers` the server sent on init
+#[derive(Debug, Clone, Default)]
+pub struct PresenceTracker {
+    /// sessions per user
+    users: Option<HashMap<OnlineUser, usize>>,
+    initial_count: usize,
+}
+
+impl PresenceTracker {
+    /// creates a tracker, `initial_count` is the online user count from init
+    pub fn new(initial_count: usize) -> Self {
+        Self {
+            users: None,
+            initial_count,
+        }
+    }
+
+    /// number of online sessions
+    pub fn count(&self) -> usize {
+        match &self.users {
+            Some(users) => users.values().sum(),
+            None => self.initial_count,
+        }
+    }
+
+    /// users of the last snapshot, once each even with more sessions. empty until the first snapshot
+    pub fn users(&self) -> Vec<OnlineUser> {
+        match &self.users {
+            Some(users) => users.keys().cloned().collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// replaces the snapshot and returns who joined and left since the last one.
+    /// the first snapshot only sets the baseline and returns no events
+    pub fn update(&mut self, snapshot: Vec<OnlineUser>) -> Vec<PresenceEvent> {
+        let mut sessions: HashMap<OnlineUser, usize> = HashMap::new();
+        for user in snapshot {
+            *sessions.entry(user).or_default() += 1;
+        }
+        let events = match &self.users {
+            Some(previous) => {
+                let mut events: Vec<PresenceEvent> = previous
+                    .keys()
+                    .filter(|user| !sessions.contains_key(*user))
+                    .cloned()
+                    .map(PresenceEvent::Left)
+                    .collect();
+                events.extend(
+                    sessions
+                        .keys()
+                        .filter(|user| !previous.contains_key(*user))
+                        .cloned()
+                        .map(PresenceEvent::Joined),
+                );
+                events
+            }
+            None => Vec::new(),
+        };
+        self.users = Some(sessions);
+        events
+    }
+}
@@ -0,0 +1,152 @@
+/*!
+unofficial [keyauth](https://keyauth.cc) seller api <https://keyauth.readme.io/reference/seller-api>
+
+the seller api manages an application from the server side, it is meant for admin tools, bots and release scripts.
+**never ship your seller key inside a client binary**, anyone who has it can do everything the dashboard can.
+
+basic usage:
+```rust,ignore
+let seller = keyauth::seller::SellerApi::new("seller key", "https://keyauth.win/api/seller/");
+let keys = seller.generate_keys(&GenerateKeys { amount: 10, expiry_days: 30, ..Default::default() }).await.inner().unwrap();
+```
+*/
+
+mod blacklist;
+mod chat;
+mod files;
+mod license;
+mod sessions;
+mod settings;
+mod users;
+mod vars;
+mod webhooks;
+
+pub use blacklist::*;
+pub use files::*;
+pub use license::*;
+pub use sessions::*;
+pub use settings::*;
+pub use users::*;
+pub use vars::*;
+pub use webhooks::*;
+
+use crate::credentials::Credential;
+use crate::Res;
+use reqwest::Client;
+use serde::{Deserialize, Deserializer};
+
+/// seller api client, every function returns Err with the server message when keyauth answers with success false
+#[derive(Clone)]
+pub struct SellerApi {
+    seller_key: Credential,
+    pub api_url: String,
+    client: Client,
+}
+
+impl SellerApi {
+    /// creates a new SellerApi, api_url is the seller endpoint, example: "https://keyauth.win/api/seller/"
+    pub fn new(seller_key: &str, api_url: &str) -> Self {
+        Self {
+            seller_key: Credential::plain(seller_key),
+            api_url: api_url.to_string(),
+            client: Client::new(),
+        }
+    }
+
+    /// sends a request of type `ty` and returns the json response if it was successful
+    async fn request(&self, ty: &str, params: &[(&str, String)]) -> Res<serde_json::Value> {
+        let text = match self.send(ty, params, "json").await.inner() {
+            Ok(text) => text,
+            Err(msg) => return Res(Err(msg)),
+        };
+        let json_rep: serde_json::Value = match serde_json::from_str(&text) {
+            Ok(json_rep) => json_rep,
+            Err(_) => return Res(Err(text)),
+        };
+        if json_rep["success"].as_bool().unwrap_or(false) {
+            Res(Ok(json_rep))
+        } else {
+            Res(Err(json_rep["message"]
+                .as_str()
+                .unwrap_or("unknown error")
+                .to_string()))
+        }
+    }
+
+    /// like request but for calls that only return a message
+    async fn request_message(&self, ty: &str, params: &[(&str, String)]) -> Res<String> {
+        match self.request(ty, params).await.inner() {
+            Ok(json_rep) => Res(Ok(json_rep["message"].as_str().unwrap_or("").to_string())),
+            Err(msg) => Res(Err(msg)),
+        }
+    }
+
+    /// sends a request and returns the raw body, `format` is the response format keyauth should use
+    async fn send(&self, ty: &str, params: &[(&str, String)], format: &str) -> Res<String> {
+        let seller_key = self.seller_key.reveal();
+        let mut query: Vec<(&str, &str)> =
+            vec![("sellerkey", &seller_key), ("type", ty), ("format", format)];
+        query.extend(params.iter().map(|(k, v)| (*k, v.as_str())));
+        let res = self
+            .client
+            .get(&self.api_url)
+            .query(&query)
+            .header("User-Agent", "KeyAuth")
+            .send()
+            .await;
+        // the url has the seller key in its query, so it is left out of the errors
+        match res {
+            Ok(res) => match res.text().await {
+                Ok(text) => Res(Ok(text)),
+                Err(e) => Res(Err(e.without_url().to_string())),
+            },
+            Err(e) => Res(Err(e.without_url().to_string())),
+        }
+    }
+}
+
+/// takes `json_rep[key]` and deserializes it, a missing key is an empty list
+fn parse_list<T: serde::de::DeserializeOwned>(
+    json_rep: &serde_json::Value,
+    key: &str,
+) -> Res<Vec<T>> {
+    if json_rep[key].is_null() {
+        return Res(Ok(Vec::new()));
+    }
+    match serde_json::from_value(json_rep[key].clone()) {
+        Ok(list) => Res(Ok(list)),
+        Err(e) => Res(Err(e.to_string())),
+    }
+}
+
+/// keyauth sends most values as strings but some versions send numbers or null, this accepts all of them
+fn de_string<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Null => String::new(),
+        serde_json::Value::String(s) => s,
+        other => other.to_string(),
+    })
+}
+
+/// same as de_string but for numbers, anything that isnt a number is 0
+fn de_u64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Number(n) => n.as_u64().unwrap_or(0),
+        serde_json::Value::String(s) => s.trim().parse().unwrap_or(0),
+        _ => 0,
+    })
+}
+
+/// keyauth sends flags as true, 1, "true" or "1" depending on the endpoint, anything else is false
+fn de_bool<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Bool(b) => b,
+        serde_json::Value::Number(n) => n.as_u64() == Some(1),
+        serde_json::Value::String(s) => matches!(s.trim(), "1" | "true"),
+        _ => false,
+    })
+}
+
+fn bool_param(value: bool) -> String {
+    if value { "1" } else { "0" }.to_string()
+}
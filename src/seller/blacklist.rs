@@ -0,0 +1,60 @@
+//! ip and hwid blacklist, blacklisted clients are reported by checkblacklist
+
+use super::{de_string, parse_list, SellerApi};
+use crate::Res;
+use serde::Deserialize;
+
+/// what a blacklist entry matches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlacklistKind {
+    Ip,
+    Hwid,
+}
+
+impl BlacklistKind {
+    fn param(self) -> &'static str {
+        match self {
+            BlacklistKind::Ip => "ip",
+            BlacklistKind::Hwid => "hwid",
+        }
+    }
+}
+
+/// a blacklist entry as returned by fetchallblacks
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct BlacklistEntry {
+    /// the blacklisted ip or hwid
+    #[serde(deserialize_with = "de_string")]
+    pub data: String,
+    /// "ip" or "hwid"
+    #[serde(default, rename = "type", deserialize_with = "de_string")]
+    pub kind: String,
+}
+
+impl SellerApi {
+    /// blacklists an ip or hwid
+    pub async fn add_blacklist(&self, kind: BlacklistKind, data: &str) -> Res<String> {
+        self.request_message("black", &[(kind.param(), data.to_string())])
+            .await
+    }
+
+    /// removes an ip or hwid from the blacklist
+    pub async fn remove_blacklist(&self, kind: BlacklistKind, data: &str) -> Res<String> {
+        self.request_message(
+            "delblack",
+            &[
+                ("data", data.to_string()),
+                ("blacktype", kind.param().to_string()),
+            ],
+        )
+        .await
+    }
+
+    /// lists all blacklist entries of the application
+    pub async fn fetch_all_blacklists(&self) -> Res<Vec<BlacklistEntry>> {
+        match self.request("fetchallblacks", &[]).await.inner() {
+            Ok(json_rep) => parse_list(&json_rep, "blacklists"),
+            Err(msg) => Res(Err(msg)),
+        }
+    }
+}
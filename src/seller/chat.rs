@@ -0,0 +1,55 @@
+//! chat moderation, the seller side of get_chat and send_chat_message
+
+use super::SellerApi;
+use crate::Res;
+use std::time::Duration;
+
+impl SellerApi {
+    /// creates a chat channel, users have to wait `delay` between messages
+    pub async fn create_channel(&self, name: &str, delay: Duration) -> Res<String> {
+        self.request_message(
+            "addchannel",
+            &[
+                ("name", name.to_string()),
+                ("delay", delay.as_secs().to_string()),
+            ],
+        )
+        .await
+    }
+
+    /// deletes a chat channel and its messages
+    pub async fn delete_channel(&self, name: &str) -> Res<String> {
+        self.request_message("delchannel", &[("name", name.to_string())])
+            .await
+    }
+
+    /// deletes every message in a channel
+    pub async fn clear_channel(&self, name: &str) -> Res<String> {
+        self.request_message("clearchannel", &[("name", name.to_string())])
+            .await
+    }
+
+    /// mutes a user in every channel for `duration`
+    pub async fn mute_user(&self, username: &str, duration: Duration) -> Res<String> {
+        self.request_message(
+            "muteuser",
+            &[
+                ("user", username.to_string()),
+                ("time", duration.as_secs().to_string()),
+            ],
+        )
+        .await
+    }
+
+    /// unmutes a user
+    pub async fn unmute_user(&self, username: &str) -> Res<String> {
+        self.request_message("unmuteuser", &[("user", username.to_string())])
+            .await
+    }
+
+    /// deletes a single chat message
+    pub async fn delete_chat_message(&self, message_id: &str) -> Res<String> {
+        self.request_message("delmsg", &[("id", message_id.to_string())])
+            .await
+    }
+}
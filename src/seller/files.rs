@@ -0,0 +1,144 @@
+//! file management
+//!
+//! keyauth doesnt take the file contents, it downloads the file from the url you give it once and serves it to clients
+//! from then on. so a release pipeline uploads the build somewhere first (a release asset, a bucket...) and passes that url.
+//! `upload_path`/`upload_reader` do both steps: they stream the build to an url that takes a PUT (a presigned bucket
+//! url...) and then hand keyauth the url it can be downloaded from
+
+use super::{bool_param, de_string, parse_list, SellerApi};
+use crate::Res;
+use serde::Deserialize;
+use std::path::Path;
+use tokio::io::AsyncRead;
+use tokio_util::io::ReaderStream;
+
+/// a file as returned by fetchallfiles
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct File {
+    /// id the client passes to file
+    #[serde(deserialize_with = "de_string")]
+    pub id: String,
+    #[serde(default, deserialize_with = "de_string")]
+    pub name: String,
+    /// url the file was uploaded from
+    #[serde(default, deserialize_with = "de_string")]
+    pub url: String,
+    #[serde(default, deserialize_with = "de_string")]
+    pub size: String,
+    #[serde(default, deserialize_with = "de_string")]
+    pub uploaddate: String,
+    /// "1" if the client has to be logged in to download it
+    #[serde(default, deserialize_with = "de_string")]
+    pub authed: String,
+}
+
+impl SellerApi {
+    /// makes keyauth download the file at `url`, if `authed` clients have to be logged in to download it.
+    /// returns the id of the new file
+    pub async fn upload_file(&self, url: &str, authed: bool) -> Res<String> {
+        let json_rep = match self
+            .request(
+                "upload",
+                &[("url", url.to_string()), ("authed", bool_param(authed))],
+            )
+            .await
+            .inner()
+        {
+            Ok(json_rep) => json_rep,
+            Err(msg) => return Res(Err(msg)),
+        };
+        match &json_rep["id"] {
+            serde_json::Value::Null => Res(Err(format!(
+                "response has no file id: {}",
+                json_rep["message"].as_str().unwrap_or("")
+            ))),
+            serde_json::Value::String(id) => Res(Ok(id.clone())),
+            id => Res(Ok(id.to_string())),
+        }
+    }
+
+    /// streams the file at `path` to `put_url` and then uploads `download_url` like upload_file, returns the id of the
+    /// new file. `download_url` is where keyauth can download what was put to `put_url`, often the same url
+    pub async fn upload_path(
+        &self,
+        put_url: &str,
+        download_url: &str,
+        path: impl AsRef<Path>,
+        authed: bool,
+    ) -> Res<String> {
+        let file = match tokio::fs::File::open(path).await {
+            Ok(file) => file,
+            Err(e) => return Res(Err(e.to_string())),
+        };
+        let len = file.metadata().await.ok().map(|meta| meta.len());
+        self.put_and_upload(put_url, download_url, file, len, authed)
+            .await
+    }
+
+    /// same as upload_path but reads the file from `reader`, it is sent chunked as it is read
+    pub async fn upload_reader<R: AsyncRead + Send + 'static>(
+        &self,
+        put_url: &str,
+        download_url: &str,
+        reader: R,
+        authed: bool,
+    ) -> Res<String> {
+        self.put_and_upload(put_url, download_url, reader, None, authed)
+            .await
+    }
+
+    /// uploads `url` and then deletes the file `file_id`, returns the id of the new file.
+    /// keyauth gives the new file a new id so clients have to be updated to use it.
+    /// the old file is kept when the upload fails
+    pub async fn replace_file(&self, file_id: &str, url: &str, authed: bool) -> Res<String> {
+        let id = match self.upload_file(url, authed).await.inner() {
+            Ok(id) => id,
+            Err(msg) => return Res(Err(msg)),
+        };
+        match self.delete_file(file_id).await.inner() {
+            Ok(_) => Res(Ok(id)),
+            Err(msg) => Res(Err(format!(
+                "uploaded {} but couldnt delete {}: {}",
+                id, file_id, msg
+            ))),
+        }
+    }
+
+    /// deletes a file
+    pub async fn delete_file(&self, file_id: &str) -> Res<String> {
+        self.request_message("delfile", &[("fileid", file_id.to_string())])
+            .await
+    }
+
+    async fn put_and_upload<R: AsyncRead + Send + 'static>(
+        &self,
+        put_url: &str,
+        download_url: &str,
+        reader: R,
+        len: Option<u64>,
+        authed: bool,
+    ) -> Res<String> {
+        let mut req = self
+            .client
+            .put(put_url)
+            .body(reqwest::Body::wrap_stream(ReaderStream::new(reader)));
+        if let Some(len) = len {
+            req = req.header(reqwest::header::CONTENT_LENGTH, len);
+        }
+        // a presigned url has its signature in the query, so it is left out of the errors
+        match req.send().await {
+            Ok(res) if res.status().is_success() => {}
+            Ok(res) => return Res(Err(format!("put failed: {}", res.status()))),
+            Err(e) => return Res(Err(e.without_url().to_string())),
+        }
+        self.upload_file(download_url, authed).await
+    }
+
+    /// lists all files of the application
+    pub async fn fetch_all_files(&self) -> Res<Vec<File>> {
+        match self.request("fetchallfiles", &[]).await.inner() {
+            Ok(json_rep) => parse_list(&json_rep, "files"),
+            Err(msg) => Res(Err(msg)),
+        }
+    }
+}
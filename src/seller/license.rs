@@ -0,0 +1,188 @@
+//! license key management
+
+use super::{bool_param, de_string, parse_list, SellerApi};
+use crate::Res;
+use serde::Deserialize;
+
+/// characters used for the `*` in a key mask
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyCharacters {
+    #[default]
+    Mixed,
+    Uppercase,
+    Lowercase,
+}
+
+impl KeyCharacters {
+    fn param(self) -> String {
+        match self {
+            KeyCharacters::Mixed => "1",
+            KeyCharacters::Uppercase => "2",
+            KeyCharacters::Lowercase => "3",
+        }
+        .to_string()
+    }
+}
+
+/// options for generate_keys
+#[derive(Debug, Clone)]
+pub struct GenerateKeys {
+    /// how many keys to generate
+    pub amount: u32,
+    /// how long the key lasts once it is used
+    pub expiry_days: u32,
+    /// subscription level the key grants
+    pub level: u32,
+    /// key format, every `*` is replaced with a random character
+    pub mask: String,
+    pub characters: KeyCharacters,
+    pub note: Option<String>,
+    /// shown as the creator of the keys in the dashboard
+    pub owner: String,
+}
+
+impl Default for GenerateKeys {
+    fn default() -> Self {
+        Self {
+            amount: 1,
+            expiry_days: 1,
+            level: 1,
+            mask: "******-******-******-******-******-******".to_string(),
+            characters: KeyCharacters::Mixed,
+            note: None,
+            owner: "SellerAPI".to_string(),
+        }
+    }
+}
+
+/// a license key as returned by fetchallkeys
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct License {
+    #[serde(default, deserialize_with = "de_string")]
+    pub id: String,
+    pub key: String,
+    #[serde(default, deserialize_with = "de_string")]
+    pub note: String,
+    /// duration of the key in seconds
+    #[serde(default, deserialize_with = "de_string")]
+    pub expires: String,
+    /// "Not Used", "Used" or "Banned"
+    #[serde(default, deserialize_with = "de_string")]
+    pub status: String,
+    #[serde(default, deserialize_with = "de_string")]
+    pub level: String,
+    /// who generated the key
+    #[serde(default, deserialize_with = "de_string")]
+    pub genby: String,
+    /// unix timestamp the key was generated at
+    #[serde(default, deserialize_with = "de_string")]
+    pub gendate: String,
+    /// unix timestamp the key was used at
+    #[serde(default, deserialize_with = "de_string")]
+    pub usedon: String,
+    /// user that used the key
+    #[serde(default, deserialize_with = "de_string")]
+    pub usedby: String,
+    /// ban reason, empty if not banned
+    #[serde(default, deserialize_with = "de_string")]
+    pub banned: String,
+}
+
+impl SellerApi {
+    /// generates keys, returns the generated keys
+    pub async fn generate_keys(&self, options: &GenerateKeys) -> Res<Vec<String>> {
+        let mut params = vec![
+            ("amount", options.amount.to_string()),
+            ("expiry", options.expiry_days.to_string()),
+            ("level", options.level.to_string()),
+            ("mask", options.mask.clone()),
+            ("character", options.characters.param()),
+            ("owner", options.owner.clone()),
+        ];
+        if let Some(note) = &options.note {
+            params.push(("note", note.clone()));
+        }
+        let json_rep = match self.request("add", &params).await.inner() {
+            Ok(json_rep) => json_rep,
+            Err(msg) => return Res(Err(msg)),
+        };
+        // a single key is returned as `key`, more as `keys`
+        match json_rep["key"].as_str() {
+            Some(key) => Res(Ok(vec![key.to_string()])),
+            None => parse_list(&json_rep, "keys"),
+        }
+    }
+
+    /// lists all keys of the application
+    pub async fn fetch_all_keys(&self) -> Res<Vec<License>> {
+        match self.request("fetchallkeys", &[]).await.inner() {
+            Ok(json_rep) => parse_list(&json_rep, "keys"),
+            Err(msg) => Res(Err(msg)),
+        }
+    }
+
+    /// exports all keys as text, one key per line
+    pub async fn export_keys(&self) -> Res<String> {
+        self.send("fetchallkeys", &[], "text").await
+    }
+
+    /// checks if a key exists, Ok(false) if keyauth says it doesnt
+    pub async fn verify_key(&self, key: &str) -> Res<bool> {
+        match self
+            .request("verify", &[("key", key.to_string())])
+            .await
+            .inner()
+        {
+            Ok(_) => Res(Ok(true)),
+            Err(msg) if msg.to_lowercase().contains("not found") => Res(Ok(false)),
+            Err(msg) => Res(Err(msg)),
+        }
+    }
+
+    /// deletes a key, if `user_too` the user that used the key is deleted as well
+    pub async fn delete_key(&self, key: &str, user_too: bool) -> Res<String> {
+        self.request_message(
+            "del",
+            &[("key", key.to_string()), ("userToo", bool_param(user_too))],
+        )
+        .await
+    }
+
+    /// bans a key, if `user_too` the user that used the key is banned as well
+    pub async fn ban_key(&self, key: &str, reason: &str, user_too: bool) -> Res<String> {
+        self.request_message(
+            "ban",
+            &[
+                ("key", key.to_string()),
+                ("reason", reason.to_string()),
+                ("userToo", bool_param(user_too)),
+            ],
+        )
+        .await
+    }
+
+    /// unbans a key
+    pub async fn unban_key(&self, key: &str) -> Res<String> {
+        self.request_message("unban", &[("key", key.to_string())])
+            .await
+    }
+
+    /// adds time to every unused key
+    pub async fn add_time_to_unused_keys(&self, days: u32) -> Res<String> {
+        self.request_message("addtime", &[("time", days.to_string())])
+            .await
+    }
+
+    /// uses a key to create `user` with `password`, assigning the key to that user
+    pub async fn assign_key(&self, key: &str, user: &str, password: &str) -> Res<String> {
+        self.request_message(
+            "activate",
+            &[
+                ("key", key.to_string()),
+                ("user", user.to_string()),
+                ("pass", password.to_string()),
+            ],
+        )
+        .await
+    }
+}
@@ -0,0 +1,44 @@
+//! active sessions, the seller side of check_session and fetch_online
+
+use super::{de_string, parse_list, SellerApi};
+use crate::Res;
+use serde::Deserialize;
+
+/// a session as returned by fetchallsessions
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Session {
+    #[serde(deserialize_with = "de_string")]
+    pub id: String,
+    /// username or license key the session was authenticated with, empty if it isnt authenticated yet
+    #[serde(default, deserialize_with = "de_string")]
+    pub credential: String,
+    /// unix timestamp the session expires at
+    #[serde(default, deserialize_with = "de_string")]
+    pub expiry: String,
+    /// "1" if the session logged in
+    #[serde(default, deserialize_with = "de_string")]
+    pub validated: String,
+    #[serde(default, deserialize_with = "de_string")]
+    pub ip: String,
+}
+
+impl SellerApi {
+    /// lists all active sessions of the application
+    pub async fn fetch_all_sessions(&self) -> Res<Vec<Session>> {
+        match self.request("fetchallsessions", &[]).await.inner() {
+            Ok(json_rep) => parse_list(&json_rep, "sessions"),
+            Err(msg) => Res(Err(msg)),
+        }
+    }
+
+    /// ends a session, the client gets false from check_session afterwards
+    pub async fn kill_session(&self, session_id: &str) -> Res<String> {
+        self.request_message("kill", &[("sessid", session_id.to_string())])
+            .await
+    }
+
+    /// ends every session of the application
+    pub async fn kill_all_sessions(&self) -> Res<String> {
+        self.request_message("killall", &[]).await
+    }
+}
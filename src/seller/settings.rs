@@ -0,0 +1,153 @@
+//! application settings and statistics
+
+use super::{bool_param, de_bool, de_string, de_u64, SellerApi};
+use crate::Res;
+use serde::Deserialize;
+use std::time::Duration;
+
+/// settings of the application as returned by getsettings
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AppSettings {
+    #[serde(default, deserialize_with = "de_bool")]
+    pub enabled: bool,
+    /// users are locked to the hwid they first logged in with
+    #[serde(default, deserialize_with = "de_bool")]
+    pub hwidcheck: bool,
+    /// version init checks the client version against
+    #[serde(default, deserialize_with = "de_string")]
+    pub version: String,
+    /// download link sent to outdated clients
+    #[serde(default, deserialize_with = "de_string")]
+    pub download: String,
+    #[serde(default, deserialize_with = "de_string")]
+    pub webdownload: String,
+    /// session expiry in seconds
+    #[serde(default, deserialize_with = "de_string")]
+    pub sessionexpiry: String,
+    #[serde(default, deserialize_with = "de_bool")]
+    pub hashcheck: bool,
+}
+
+/// settings to change with update_settings, None leaves the setting as it is
+#[derive(Debug, Clone, Default)]
+pub struct UpdateSettings {
+    pub enabled: Option<bool>,
+    pub hwid_check: Option<bool>,
+    pub hash_check: Option<bool>,
+    pub version: Option<String>,
+    pub download: Option<String>,
+    pub web_download: Option<String>,
+    pub session_expiry: Option<Duration>,
+}
+
+/// statistics of the application as returned by stats
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AppStats {
+    #[serde(default, deserialize_with = "de_u64")]
+    pub totalkeys: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    pub unused: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    pub used: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    pub paused: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    pub banned: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    pub totalusers: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    pub totalsessions: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    pub webhooks: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    pub files: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    pub vars: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    pub resellers: u64,
+    #[serde(default, deserialize_with = "de_u64")]
+    pub managers: u64,
+}
+
+impl SellerApi {
+    /// gets the settings of the application
+    pub async fn get_settings(&self) -> Res<AppSettings> {
+        let json_rep = match self.request("getsettings", &[]).await.inner() {
+            Ok(json_rep) => json_rep,
+            Err(msg) => return Res(Err(msg)),
+        };
+        match serde_json::from_value(json_rep) {
+            Ok(settings) => Res(Ok(settings)),
+            Err(e) => Res(Err(e.to_string())),
+        }
+    }
+
+    /// changes the settings that are Some
+    pub async fn update_settings(&self, settings: &UpdateSettings) -> Res<String> {
+        let mut params = Vec::new();
+        if let Some(enabled) = settings.enabled {
+            params.push(("enabled", bool_param(enabled)));
+        }
+        if let Some(hwid_check) = settings.hwid_check {
+            params.push(("hwidcheck", bool_param(hwid_check)));
+        }
+        if let Some(hash_check) = settings.hash_check {
+            params.push(("hashcheck", bool_param(hash_check)));
+        }
+        if let Some(version) = &settings.version {
+            params.push(("ver", version.clone()));
+        }
+        if let Some(download) = &settings.download {
+            params.push(("download", download.clone()));
+        }
+        if let Some(web_download) = &settings.web_download {
+            params.push(("webdownload", web_download.clone()));
+        }
+        if let Some(session_expiry) = settings.session_expiry {
+            params.push(("sessionexpiry", session_expiry.as_secs().to_string()));
+        }
+        self.request_message("updatesettings", &params).await
+    }
+
+    /// sets the version init checks against, clients with another version get "invalidver"
+    pub async fn set_version(&self, version: &str) -> Res<String> {
+        self.update_settings(&UpdateSettings {
+            version: Some(version.to_string()),
+            ..Default::default()
+        })
+        .await
+    }
+
+    /// pauses the application, this also pauses every user's subscription
+    pub async fn pause_app(&self) -> Res<String> {
+        self.request_message("pauseapp", &[]).await
+    }
+
+    /// unpauses the application and the subscriptions paused with it
+    pub async fn unpause_app(&self) -> Res<String> {
+        self.request_message("unpauseapp", &[]).await
+    }
+
+    /// removes every hash, the next init with a hash registers it
+    pub async fn reset_hash(&self) -> Res<String> {
+        self.request_message("resethash", &[]).await
+    }
+
+    /// adds a hash to the hashes init accepts, use it to register a new build
+    pub async fn add_hash(&self, hash: &str) -> Res<String> {
+        self.request_message("addhash", &[("hash", hash.to_string())])
+            .await
+    }
+
+    /// gets the statistics of the application
+    pub async fn fetch_stats(&self) -> Res<AppStats> {
+        let json_rep = match self.request("stats", &[]).await.inner() {
+            Ok(json_rep) => json_rep,
+            Err(msg) => return Res(Err(msg)),
+        };
+        match serde_json::from_value(json_rep) {
+            Ok(stats) => Res(Ok(stats)),
+            Err(e) => Res(Err(e.to_string())),
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! user and subscription administration
+
+use super::{bool_param, de_string, parse_list, SellerApi};
+use crate::Res;
+use serde::Deserialize;
+
+/// a user as returned by userdata and fetchallusers
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct User {
+    pub username: String,
+    #[serde(default, deserialize_with = "de_string")]
+    pub ip: String,
+    #[serde(default, deserialize_with = "de_string")]
+    pub hwid: String,
+    /// unix timestamp the user was created at
+    #[serde(default, deserialize_with = "de_string")]
+    pub createdate: String,
+    /// unix timestamp of the last login
+    #[serde(default, deserialize_with = "de_string")]
+    pub lastlogin: String,
+    /// ban reason, empty if not banned
+    #[serde(default, deserialize_with = "de_string")]
+    pub banned: String,
+    #[serde(default)]
+    pub subscriptions: Vec<UserSubscription>,
+}
+
+/// a subscription a user has
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct UserSubscription {
+    pub subscription: String,
+    /// key that granted the subscription
+    #[serde(default, deserialize_with = "de_string")]
+    pub key: String,
+    /// unix timestamp the subscription expires at
+    #[serde(default, deserialize_with = "de_string")]
+    pub expiry: String,
+}
+
+/// a subscription of the application
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Subscription {
+    pub name: String,
+    #[serde(default, deserialize_with = "de_string")]
+    pub level: String,
+}
+
+impl SellerApi {
+    /// creates a user with `subscription` that lasts `expiry_days`
+    pub async fn create_user(
+        &self,
+        username: &str,
+        password: &str,
+        subscription: &str,
+        expiry_days: u32,
+    ) -> Res<String> {
+        self.request_message(
+            "adduser",
+            &[
+                ("user", username.to_string()),
+                ("pass", password.to_string()),
+                ("sub", subscription.to_string()),
+                ("expiry", expiry_days.to_string()),
+            ],
+        )
+        .await
+    }
+
+    /// deletes a user
+    pub async fn delete_user(&self, username: &str) -> Res<String> {
+        self.request_message("deluser", &[("user", username.to_string())])
+            .await
+    }
+
+    /// bans a user, this also blacklists their ip and hwid
+    pub async fn ban_user(&self, username: &str, reason: &str) -> Res<String> {
+        self.request_message(
+            "banuser",
+            &[
+                ("user", username.to_string()),
+                ("reason", reason.to_string()),
+            ],
+        )
+        .await
+    }
+
+    /// unbans a user
+    pub async fn unban_user(&self, username: &str) -> Res<String> {
+        self.request_message("unbanuser", &[("user", username.to_string())])
+            .await
+    }
+
+    /// resets the hwid of a user so they can login from another machine
+    pub async fn reset_hwid(&self, username: &str) -> Res<String> {
+        self.request_message("resetuser", &[("user", username.to_string())])
+            .await
+    }
+
+    /// sets a new password for a user
+    pub async fn reset_password(&self, username: &str, new_password: &str) -> Res<String> {
+        self.request_message(
+            "resetpw",
+            &[
+                ("user", username.to_string()),
+                ("passwd", new_password.to_string()),
+            ],
+        )
+        .await
+    }
+
+    /// gets everything keyauth knows about a user
+    pub async fn fetch_user(&self, username: &str) -> Res<User> {
+        let json_rep = match self
+            .request("userdata", &[("user", username.to_string())])
+            .await
+            .inner()
+        {
+            Ok(json_rep) => json_rep,
+            Err(msg) => return Res(Err(msg)),
+        };
+        match serde_json::from_value(json_rep) {
+            Ok(user) => Res(Ok(user)),
+            Err(e) => Res(Err(e.to_string())),
+        }
+    }
+
+    /// lists all users of the application
+    pub async fn fetch_all_users(&self) -> Res<Vec<User>> {
+        match self.request("fetchallusers", &[]).await.inner() {
+            Ok(json_rep) => parse_list(&json_rep, "users"),
+            Err(msg) => Res(Err(msg)),
+        }
+    }
+
+    /// lists the subscriptions of the application
+    pub async fn fetch_all_subscriptions(&self) -> Res<Vec<Subscription>> {
+        match self.request("fetchallsubs", &[]).await.inner() {
+            Ok(json_rep) => parse_list(&json_rep, "subs"),
+            Err(msg) => Res(Err(msg)),
+        }
+    }
+
+    /// gives `username` `subscription` for `days` more, use "all" as username to extend every user.
+    /// if `active_only` only users who currently have the subscription are extended
+    pub async fn extend_subscription(
+        &self,
+        username: &str,
+        subscription: &str,
+        days: u32,
+        active_only: bool,
+    ) -> Res<String> {
+        self.request_message(
+            "extend",
+            &[
+                ("user", username.to_string()),
+                ("sub", subscription.to_string()),
+                ("expiry", days.to_string()),
+                ("activeOnly", bool_param(active_only)),
+            ],
+        )
+        .await
+    }
+
+    /// removes `subscription` from a user
+    pub async fn remove_user_subscription(
+        &self,
+        username: &str,
+        subscription: &str,
+    ) -> Res<String> {
+        self.request_message(
+            "delsub",
+            &[
+                ("user", username.to_string()),
+                ("sub", subscription.to_string()),
+            ],
+        )
+        .await
+    }
+
+    /// changes the level of a subscription, every user with it gets the new level
+    pub async fn set_subscription_level(&self, subscription: &str, level: u32) -> Res<String> {
+        self.request_message(
+            "editsub",
+            &[
+                ("sub", subscription.to_string()),
+                ("level", level.to_string()),
+            ],
+        )
+        .await
+    }
+
+    /// sets a user variable, the client reads it with getvar
+    pub async fn set_user_var(&self, username: &str, var: &str, data: &str) -> Res<String> {
+        self.request_message(
+            "setvar",
+            &[
+                ("user", username.to_string()),
+                ("var", var.to_string()),
+                ("data", data.to_string()),
+            ],
+        )
+        .await
+    }
+}
@@ -0,0 +1,56 @@
+//! global variable management, the client reads them with var
+
+use super::{bool_param, de_string, parse_list, SellerApi};
+use crate::Res;
+use serde::Deserialize;
+
+/// a global variable as returned by fetchallvars
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Var {
+    #[serde(deserialize_with = "de_string")]
+    pub varid: String,
+    /// value of the variable
+    #[serde(default, deserialize_with = "de_string")]
+    pub msg: String,
+    /// "1" if the client has to be logged in to read it
+    #[serde(default, deserialize_with = "de_string")]
+    pub authed: String,
+}
+
+impl SellerApi {
+    /// creates a global variable, if `authed` clients have to be logged in to read it
+    pub async fn create_var(&self, name: &str, data: &str, authed: bool) -> Res<String> {
+        self.request_message(
+            "addvar",
+            &[
+                ("name", name.to_string()),
+                ("data", data.to_string()),
+                ("authed", bool_param(authed)),
+            ],
+        )
+        .await
+    }
+
+    /// changes the value of a global variable
+    pub async fn update_var(&self, name: &str, data: &str) -> Res<String> {
+        self.request_message(
+            "editvar",
+            &[("varid", name.to_string()), ("data", data.to_string())],
+        )
+        .await
+    }
+
+    /// deletes a global variable
+    pub async fn delete_var(&self, name: &str) -> Res<String> {
+        self.request_message("delvar", &[("name", name.to_string())])
+            .await
+    }
+
+    /// lists all global variables of the application
+    pub async fn fetch_all_vars(&self) -> Res<Vec<Var>> {
+        match self.request("fetchallvars", &[]).await.inner() {
+            Ok(json_rep) => parse_list(&json_rep, "vars"),
+            Err(msg) => Res(Err(msg)),
+        }
+    }
+}
@@ -0,0 +1,90 @@
+//! webhook management, the client calls them with webhook
+
+use super::{bool_param, de_string, parse_list, SellerApi};
+use crate::Res;
+use serde::Deserialize;
+
+/// options for create_webhook and edit_webhook
+#[derive(Debug, Clone)]
+pub struct WebhookOptions {
+    /// url keyauth sends the request to, the client appends its params to it
+    pub base_url: String,
+    /// user agent keyauth sends the request with
+    pub user_agent: String,
+    /// if true clients have to be logged in to call it, otherwise it is public
+    pub authed: bool,
+}
+
+impl Default for WebhookOptions {
+    fn default() -> Self {
+        Self {
+            base_url: String::new(),
+            user_agent: "KeyAuth".to_string(),
+            authed: true,
+        }
+    }
+}
+
+impl WebhookOptions {
+    fn params(&self) -> Vec<(&'static str, String)> {
+        vec![
+            ("baseurl", self.base_url.clone()),
+            ("ua", self.user_agent.clone()),
+            ("authed", bool_param(self.authed)),
+        ]
+    }
+}
+
+/// a webhook as returned by fetchallwebhooks
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Webhook {
+    /// id the client passes to webhook
+    #[serde(deserialize_with = "de_string")]
+    pub webid: String,
+    #[serde(default, deserialize_with = "de_string")]
+    pub baselink: String,
+    #[serde(default, deserialize_with = "de_string")]
+    pub useragent: String,
+    /// "1" if the client has to be logged in to call it
+    #[serde(default, deserialize_with = "de_string")]
+    pub authed: String,
+}
+
+impl SellerApi {
+    /// creates a webhook, returns its id
+    pub async fn create_webhook(&self, options: &WebhookOptions) -> Res<String> {
+        let json_rep = match self.request("addwebhook", &options.params()).await.inner() {
+            Ok(json_rep) => json_rep,
+            Err(msg) => return Res(Err(msg)),
+        };
+        match &json_rep["webid"] {
+            serde_json::Value::String(webid) => Res(Ok(webid.clone())),
+            serde_json::Value::Null => Res(Err(format!(
+                "response has no webhook id: {}",
+                json_rep["message"].as_str().unwrap_or("")
+            ))),
+            webid => Res(Ok(webid.to_string())),
+        }
+    }
+
+    /// changes the url, user agent and authed flag of a webhook
+    pub async fn edit_webhook(&self, webid: &str, options: &WebhookOptions) -> Res<String> {
+        let mut params = options.params();
+        params.push(("webid", webid.to_string()));
+        self.request_message("editwebhook", &params).await
+    }
+
+    /// deletes a webhook
+    pub async fn delete_webhook(&self, webid: &str) -> Res<String> {
+        self.request_message("delwebhook", &[("webid", webid.to_string())])
+            .await
+    }
+
+    /// lists all webhooks of the application
+    pub async fn fetch_all_webhooks(&self) -> Res<Vec<Webhook>> {
+        match self.request("fetchallwebhooks", &[]).await.inner() {
+            Ok(json_rep) => parse_list(&json_rep, "webhooks"),
+            Err(msg) => Res(Err(msg)),
+        }
+    }
+}
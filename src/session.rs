@@ -0,0 +1,133 @@
+/*!
+what init and the login calls return
+
+init returns an [`AppInfo`] and login/register/license/web_login return a [`UserSession`], both are built from the whole
+response before anything on the client is touched, so a failed or partial response cant leave the client half updated.
+the loose fields on `KeyauthApi` (username, ip, num_users...) are still filled from these for older code.
+*/
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// application stats sent on init and by fetch_stats
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct AppInfo {
+    pub num_users: u64,
+    pub num_online_users: u64,
+    pub num_keys: u64,
+    /// version set in the dashboard, empty if the server didnt send it
+    pub version: String,
+    pub customer_panel_link: String,
+}
+
+impl AppInfo {
+    /// reads the `appinfo` object of a response
+    pub(crate) fn from_response(json_rep: &serde_json::Value) -> Self {
+        let info = &json_rep["appinfo"];
+        Self {
+            num_users: number(&info["numUsers"]),
+            num_online_users: number(&info["numOnlineUsers"]),
+            num_keys: number(&info["numKeys"]),
+            version: string(&info["version"]),
+            customer_panel_link: string(&info["customerPanelLink"]),
+        }
+    }
+}
+
+/// the logged in user
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct UserSession {
+    pub username: String,
+    pub ip: String,
+    pub hwid: String,
+    /// unix timestamp (seconds) the account was created at
+    pub create_date: u64,
+    /// unix timestamp (seconds) of the login before this one
+    pub last_login: u64,
+    pub subscriptions: Vec<Subscription>,
+}
+
+/// a subscription of the logged in user
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Subscription {
+    /// name of the subscription in the dashboard
+    pub subscription: String,
+    /// license key that granted it
+    pub key: String,
+    /// unix timestamp (seconds) it expires at
+    pub expiry: u64,
+    /// seconds left when the response was sent
+    pub timeleft: u64,
+}
+
+impl Subscription {
+    /// time until it expires by the system clock, `timeleft` if the server didnt send `expiry`
+    pub fn remaining(&self) -> Duration {
+        if self.expiry == 0 {
+            return Duration::from_secs(self.timeleft);
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|now| now.as_secs())
+            .unwrap_or(0);
+        Duration::from_secs(self.expiry.saturating_sub(now))
+    }
+}
+
+impl UserSession {
+    /// reads the `info` object of a response, `username` is used when the server doesnt send it back and `hwid` is the one the request was sent with
+    pub(crate) fn from_response(json_rep: &serde_json::Value, username: &str, hwid: &str) -> Self {
+        let info = &json_rep["info"];
+        let or = |value: String, default: &str| {
+            if value.is_empty() {
+                default.to_string()
+            } else {
+                value
+            }
+        };
+        Self {
+            username: or(string(&info["username"]), username),
+            ip: string(&info["ip"]),
+            hwid: hwid.to_string(),
+            create_date: number(&info["createdate"]),
+            last_login: number(&info["lastlogin"]),
+            subscriptions: info["subscriptions"]
+                .as_array()
+                .map(|subs| {
+                    subs.iter()
+                        .map(|sub| Subscription {
+                            subscription: string(&sub["subscription"]),
+                            key: string(&sub["key"]),
+                            expiry: number(&sub["expiry"]),
+                            timeleft: number(&sub["timeleft"]),
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+        }
+    }
+
+    /// name of the first subscription, what `KeyauthApi::subscription` has always been
+    pub fn subscription(&self) -> &str {
+        match self.subscriptions.first() {
+            Some(sub) => &sub.subscription,
+            None => "",
+        }
+    }
+}
+
+fn string(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+/// keyauth sends numbers as strings on most versions, anything that isnt a number is 0
+fn number(value: &serde_json::Value) -> u64 {
+    match value {
+        serde_json::Value::Number(n) => n.as_u64().unwrap_or(0),
+        serde_json::Value::String(s) => s.trim().parse().unwrap_or(0),
+        _ => 0,
+    }
+}
@@ -0,0 +1,110 @@
+//! tracing spans for the api requests, everything in here does nothing without the `tracing` feature
+//!
+//! every request gets a `keyauth_request` span with the request type, the parameters (only the ones below, everything
+//! else like passwords, keys and the application credentials is `<redacted>`), the http status, latency and the result.
+//! `error` is one of network, invalid_application, rejected (keyauth said no, the server message is in `reason`),
+//! tampered or invalid_response (the body wasnt json).
+
+#[cfg(feature = "tracing")]
+use std::fmt::Write;
+use std::future::Future;
+use std::time::Duration;
+
+/// parameters that are shown as is
+#[cfg(feature = "tracing")]
+const SHOWN: [&str; 8] = [
+    "type", "username", "hwid", "ver", "hash", "varid", "fileid", "channel",
+];
+
+#[derive(Clone)]
+#[cfg_attr(not(feature = "tracing"), derive(Default))]
+pub(crate) struct RequestSpan {
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+#[cfg(feature = "tracing")]
+impl Default for RequestSpan {
+    fn default() -> Self {
+        Self {
+            span: tracing::Span::none(),
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl RequestSpan {
+    pub(crate) fn new<'a>(params: impl Iterator<Item = (&'a str, &'a str)>) -> Self {
+        let mut ty = "";
+        let mut shown = String::new();
+        for (key, value) in params {
+            if key == "type" {
+                ty = value;
+            }
+            let value = if SHOWN.contains(&key) {
+                value
+            } else {
+                "<redacted>"
+            };
+            let _ = write!(shown, "{}={} ", key, value);
+        }
+        Self {
+            span: tracing::info_span!(
+                "keyauth_request",
+                r#type = ty,
+                params = shown.trim_end(),
+                status = tracing::field::Empty,
+                latency_ms = tracing::field::Empty,
+                success = tracing::field::Empty,
+                error = tracing::field::Empty,
+                reason = tracing::field::Empty,
+            ),
+        }
+    }
+
+    /// runs the request inside the span
+    pub(crate) fn instrument<F: Future>(&self, fut: F) -> impl Future<Output = F::Output> {
+        tracing::Instrument::instrument(fut, self.span.clone())
+    }
+
+    pub(crate) fn sent(&self, status: u16, latency: Duration) {
+        self.span.record("status", status);
+        self.span.record("latency_ms", latency.as_millis() as u64);
+    }
+
+    /// records success of the json response and the message if keyauth said no.
+    /// the message of a successful response can be a var, getvar or webhook value so it isnt recorded
+    pub(crate) fn json(&self, json_rep: &serde_json::Value) {
+        let success = json_rep["success"].as_bool().unwrap_or(false);
+        self.span.record("success", success);
+        if !success {
+            self.span.record("error", "rejected");
+            self.span
+                .record("reason", json_rep["message"].as_str().unwrap_or(""));
+        }
+    }
+
+    pub(crate) fn failed(&self, error: &str, message: &str) {
+        self.span.record("success", false);
+        self.span.record("error", error);
+        self.span.record("reason", message);
+        tracing::warn!(parent: &self.span, error, "keyauth request failed: {}", message);
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+impl RequestSpan {
+    pub(crate) fn new<'a>(_params: impl Iterator<Item = (&'a str, &'a str)>) -> Self {
+        Self {}
+    }
+
+    pub(crate) fn instrument<F: Future>(&self, fut: F) -> impl Future<Output = F::Output> {
+        fut
+    }
+
+    pub(crate) fn sent(&self, _status: u16, _latency: Duration) {}
+
+    pub(crate) fn json(&self, _json_rep: &serde_json::Value) {}
+
+    pub(crate) fn failed(&self, _error: &str, _message: &str) {}
+}
@@ -0,0 +1,123 @@
+//! request plumbing shared by the api versions
+
+use crate::trace::RequestSpan;
+use bytes::Bytes;
+use goldberg::goldberg_stmts;
+use reqwest::header::HeaderMap;
+use reqwest::Client;
+use std::time::Instant;
+use zeroize::{Zeroize, Zeroizing};
+
+pub struct Resp {
+    pub(crate) res: String,
+    pub(crate) head: HeaderMap,
+}
+
+/// request fields before the api version encodes them, they hold passwords and keys so the values are zeroed on drop
+pub struct Data(pub(crate) Vec<(String, String)>);
+
+impl Data {
+    pub(crate) fn insert<T: ToString, G: ToString>(&mut self, key: T, val: G) {
+        self.0.push((key.to_string(), val.to_string()));
+    }
+
+    /// the key value pairs, for the request span
+    pub(crate) fn params(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(key, val)| (key.as_str(), val.as_str()))
+    }
+
+    /// the fields as they are, form encoded
+    #[cfg_attr(
+        not(any(feature = "v1_1", feature = "v1_2", feature = "v1_3")),
+        allow(dead_code)
+    )]
+    pub(crate) fn form(&self) -> Body {
+        let len = self
+            .0
+            .iter()
+            .map(|(key, val)| Body::field_len(key, val))
+            .sum();
+        let mut body = Body(String::with_capacity(len));
+        for (key, val) in &self.0 {
+            body.insert(key, val);
+        }
+        body
+    }
+}
+
+impl Drop for Data {
+    fn drop(&mut self) {
+        for (_, val) in &mut self.0 {
+            val.zeroize();
+        }
+    }
+}
+
+/// form encoded request body, it holds passwords and keys so it is zeroed on drop
+pub struct Body(pub(crate) String);
+
+impl Body {
+    /// appends the field, the value is percent encoded so `+`, `&` and `=` in it arrive as they are
+    pub(crate) fn insert(&mut self, key: &str, val: &str) {
+        let len = Self::field_len(key, val);
+        // letting the string grow would free the old buffer with the earlier fields still in it
+        if self.0.capacity() - self.0.len() < len {
+            let mut grown = String::with_capacity((self.0.len() + len) * 2);
+            grown.push_str(&self.0);
+            self.0.zeroize();
+            self.0 = grown;
+        }
+        if !self.0.is_empty() {
+            self.0.push('&');
+        }
+        self.0.push_str(key);
+        self.0.push('=');
+        self.0
+            .extend(form_urlencoded::byte_serialize(val.as_bytes()));
+    }
+
+    /// bytes the field takes in the body, with the `&` before it
+    fn field_len(key: &str, val: &str) -> usize {
+        let val_len: usize = form_urlencoded::byte_serialize(val.as_bytes())
+            .map(str::len)
+            .sum();
+        key.len() + val_len + 2
+    }
+}
+
+impl Drop for Body {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+/// posts the body to the api inside `span`
+pub(crate) async fn send(mut body: Body, url: &str, span: &RequestSpan) -> Result<Resp, String> {
+    let start = Instant::now();
+    let res: Result<Resp, String> = goldberg_stmts! {{
+    let client = Client::new();
+    // reqwest gets bytes that are zeroed when it drops them, copies hyper makes while writing them to the socket arent
+    let body_bytes = Bytes::from_owner(Zeroizing::new(std::mem::take(&mut body.0).into_bytes()));
+        nodebug!();
+    let res = match span.instrument(client.post(url.to_string())
+        .body(body_bytes)
+        .header("User-Agent", "KeyAuth")
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .send()).await {
+        Ok(res) => res,
+        Err(e) => {
+            span.failed("network", &e.to_string());
+            return Err(e.to_string());
+        }
+    };
+    span.sent(res.status().as_u16(), start.elapsed());
+    let head = res.headers().clone();
+    match span.instrument(res.text()).await {
+        Ok(text) => Ok(Resp { head: head, res: text }),
+        Err(e) => {
+            span.failed("network", &e.to_string());
+            Err(e.to_string())
+        }
+    }}};
+    res
+}
@@ -0,0 +1,94 @@
+/*!
+two factor authentication
+
+when 2fa is enabled for a user keyauth refuses the login until a totp code is sent with it, login returns
+[`LoginError::TwoFactorRequired`] so you can ask the user for the code and call login again with `Some(code)`.
+
+enrolling a user:
+```rust,ignore
+let setup = auth.enable_2fa().await.inner().unwrap();
+// show setup.uri as a qr code (or setup.secret) so the user can add it to their authenticator app
+auth.confirm_2fa(code_from_the_app).await.inner().unwrap();
+```
+*/
+
+use reqwest::Url;
+use std::fmt;
+
+/// errors returned by login
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LoginError {
+    /// the user has 2fa enabled, call login again with the code from their authenticator app
+    TwoFactorRequired,
+    /// the 2fa code was wrong or expired
+    InvalidTwoFactorCode,
+    /// the response failed the signature check
+    Tampered,
+    /// any other error, contains the message returned by the server
+    Other(String),
+}
+
+impl fmt::Display for LoginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoginError::TwoFactorRequired => write!(f, "2fa code required"),
+            LoginError::InvalidTwoFactorCode => write!(f, "invalid 2fa code"),
+            LoginError::Tampered => write!(f, "response was tampered with"),
+            LoginError::Other(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for LoginError {}
+
+impl From<String> for LoginError {
+    /// maps the message the server sent back to a typed error
+    fn from(msg: String) -> Self {
+        let lower = msg.to_lowercase();
+        if !lower.contains("2fa") {
+            LoginError::Other(msg)
+        } else if lower.contains("invalid") || lower.contains("incorrect") {
+            LoginError::InvalidTwoFactorCode
+        } else if lower.contains("required") || lower.contains("enter") || lower.contains("missing")
+        {
+            LoginError::TwoFactorRequired
+        } else {
+            LoginError::Other(msg)
+        }
+    }
+}
+
+/// what an authenticator app needs to generate codes for the user
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TwoFactorSetup {
+    /// base32 totp secret, for typing into the app by hand
+    pub secret: String,
+    /// `otpauth://` uri, render it as a qr code
+    pub uri: String,
+}
+
+impl TwoFactorSetup {
+    /// reads the `2fa` object of a `2faenable` response, builds the uri if the server didnt send one
+    pub(crate) fn from_response(
+        json_rep: &serde_json::Value,
+        app: &str,
+        username: &str,
+    ) -> Result<Self, String> {
+        let secret = match json_rep["2fa"]["secret_code"].as_str() {
+            Some(secret) if !secret.is_empty() => secret.to_string(),
+            _ => return Err("response is missing the 2fa secret".to_string()),
+        };
+        let uri = match json_rep["2fa"]["QRCode"].as_str() {
+            Some(uri) if uri.starts_with("otpauth://") => uri.to_string(),
+            _ => {
+                let mut uri = Url::parse("otpauth://totp/").map_err(|e| e.to_string())?;
+                uri.set_path(&format!("{}:{}", app, username));
+                uri.query_pairs_mut()
+                    .append_pair("secret", &secret)
+                    .append_pair("issuer", app);
+                uri.to_string()
+            }
+        };
+        Ok(Self { secret, uri })
+    }
+}
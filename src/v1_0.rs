@@ -0,0 +1,140 @@
+/*!
+unofficial [keyauth](https://keyauth.cc) library that uses 1.0 api version
+
+only use this for applications that are still on 1.0. every request and response is aes-256-cbc encrypted,
+init with the application secret and everything after it with a random session key sent on init.
+use v1_2 or v1_3 for new applications.
+
+basic usage:
+```rust,ignore
+let mut auth = keyauth::v1_0::KeyauthApi::new("application name", "ownerid", "application secret", "application version", "api url"); // if you dont have a custom domain for api use "https://keyauth.win/api/1.0/"
+auth.init(None).await.inner().unwrap();
+auth.login("username".to_string(), "password".to_string(), None, None).await.inner().unwrap();
+```
+*/
+
+pub use crate::{KeyauthClient, Res};
+
+use crate::api::Protocol;
+use crate::credentials::{AppCredentials, Credential};
+use crate::transport::{Body, Data, Resp};
+use aes::cipher::block_padding::Pkcs7;
+use aes::cipher::generic_array::GenericArray;
+use aes::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use aes::Aes256;
+use hmac_sha256::Hash;
+use uuid::Uuid;
+use zeroize::Zeroizing;
+
+/// fields that are only hex encoded, everything else except the hash gets encrypted
+const PLAIN_FIELDS: [&str; 4] = ["type", "sessionid", "name", "ownerid"];
+
+/// every function in this struct (accept log and ban) returns a Result with the server message as error
+pub type KeyauthApi = crate::api::KeyauthApi<Aes>;
+
+/// how the 1.0 api encrypts requests and responses: aes-256-cbc with a fresh iv per request, keyed with the application
+/// secret for init and with the session key after it
+#[derive(Default, Clone)]
+pub struct Aes {
+    secret: Credential,
+    enckey: Credential,
+}
+
+impl Protocol for Aes {
+    /// the iv of the request, the response is encrypted with it too
+    type Nonce = String;
+
+    fn start_session(&mut self, init: &mut Data) {
+        self.enckey = Credential::from(Uuid::new_v4().simple().to_string());
+        init.insert("enckey", self.enckey.reveal().as_str());
+    }
+
+    fn encode(&self, data: &Data, init: bool) -> (Body, String) {
+        let key = if init {
+            self.secret.reveal()
+        } else {
+            self.enckey.reveal()
+        };
+        let iv = hex::encode(Hash::hash(Uuid::new_v4().to_string().as_bytes()));
+        let mut body = Body(String::new());
+        for (field, value) in &data.0 {
+            if PLAIN_FIELDS.contains(&field.as_str()) {
+                body.insert(field, &hex::encode(value));
+            } else if field == "hash" {
+                body.insert(field, value);
+            } else {
+                body.insert(field, &encrypt(value, &key, &iv));
+            }
+        }
+        body.insert("init_iv", &iv);
+        (body, iv)
+    }
+
+    fn verify(&self, resp: &Resp, iv: String, init: bool) -> Result<String, String> {
+        let key = if init {
+            self.secret.reveal()
+        } else {
+            self.enckey.reveal()
+        };
+        decrypt(&resp.res, &key, &iv).ok_or_else(|| "couldnt decrypt the response".to_string())
+    }
+}
+
+impl KeyauthApi {
+    /// creats a new KeyauthApi and its defaults, api_url has to be api version 1.0 example: "https://keyauth.win/api/1.0/" or if you have a custom api domain: "https://api.example.com/1.0/"
+    pub fn new(name: &str, owner_id: &str, secret: &str, version: &str, api_url: &str) -> Self {
+        Self::from_app(
+            AppCredentials {
+                name: Credential::plain(name),
+                owner_id: Credential::plain(owner_id),
+                secret: Credential::plain(secret),
+                version: version.to_string(),
+            },
+            api_url,
+        )
+    }
+
+    /// same as new but with the credentials from keyauth_app!, they stay encrypted in memory
+    pub fn from_app(app: AppCredentials, api_url: &str) -> Self {
+        let protocol = Aes {
+            secret: app.secret,
+            ..Aes::default()
+        };
+        Self::with_protocol(protocol, app.name, app.owner_id, &app.version, api_url)
+    }
+}
+
+/// the 1.0 api derives the aes key and iv from the first characters of their sha256 hex digest.
+/// the key material is zeroed on drop
+fn derive(key: &str, iv: &str) -> (Zeroizing<[u8; 32]>, Zeroizing<[u8; 16]>) {
+    let mut aes_key = Zeroizing::new([0u8; 32]);
+    let mut aes_iv = Zeroizing::new([0u8; 16]);
+    let key_hex = Zeroizing::new(hex::encode(Zeroizing::new(Hash::hash(key.as_bytes()))));
+    aes_key.copy_from_slice(&key_hex.as_bytes()[..32]);
+    aes_iv.copy_from_slice(&hex::encode(Hash::hash(iv.as_bytes())).as_bytes()[..16]);
+    (aes_key, aes_iv)
+}
+
+/// aes-256-cbc with pkcs7 padding, returns hex
+fn encrypt(message: &str, key: &str, iv: &str) -> String {
+    let (aes_key, aes_iv) = derive(key, iv);
+    let ciphertext = cbc::Encryptor::<Aes256>::new(
+        GenericArray::from_slice(&aes_key[..]),
+        GenericArray::from_slice(&aes_iv[..]),
+    )
+    .encrypt_padded_vec_mut::<Pkcs7>(message.as_bytes());
+    hex::encode(ciphertext)
+}
+
+/// reverses encrypt, None if the message isnt valid hex or the padding is wrong
+fn decrypt(message: &str, key: &str, iv: &str) -> Option<String> {
+    let (aes_key, aes_iv) = derive(key, iv);
+    let ciphertext = hex::decode(message.trim()).ok()?;
+    let plaintext = cbc::Decryptor::<Aes256>::new(
+        GenericArray::from_slice(&aes_key[..]),
+        GenericArray::from_slice(&aes_iv[..]),
+    )
+    .decrypt_padded_vec_mut::<Pkcs7>(&ciphertext)
+    .ok()?;
+    String::from_utf8(plaintext).ok()
+}
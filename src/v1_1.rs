@@ -0,0 +1,56 @@
+/*!
+unofficial [keyauth](https://keyauth.cc) library that uses 1.1 api version
+
+only use this for applications that are still on 1.1, the 1.1 api doesnt sign its responses so they can be tampered with.
+use v1_2 or v1_3 for new applications.
+
+basic usage:
+```rust,ignore
+let mut auth = keyauth::v1_1::KeyauthApi::new("application name", "ownerid", "application version", "api url"); // if you dont have a custom domain for api use "https://keyauth.win/api/1.1/"
+auth.init(None).await.inner().unwrap();
+auth.login("username".to_string(), "password".to_string(), None, None).await.inner().unwrap();
+```
+*/
+
+pub use crate::{KeyauthClient, Res};
+
+use crate::api::Protocol;
+use crate::credentials::{AppCredentials, Credential};
+use crate::transport::{Body, Data, Resp};
+
+/// every function in this struct (accept log and ban) returns a Result with the server message as error
+pub type KeyauthApi = crate::api::KeyauthApi<Unsigned>;
+
+/// the 1.1 api sends everything as is and doesnt sign its responses
+#[derive(Default, Clone)]
+pub struct Unsigned;
+
+impl Protocol for Unsigned {
+    type Nonce = ();
+
+    fn encode(&self, data: &Data, _init: bool) -> (Body, ()) {
+        (data.form(), ())
+    }
+
+    fn verify(&self, resp: &Resp, _nonce: (), _init: bool) -> Result<String, String> {
+        Ok(resp.res.clone())
+    }
+}
+
+impl KeyauthApi {
+    /// creats a new KeyauthApi and its defaults, api_url has to be api version 1.1 example: "https://keyauth.win/api/1.1/" or if you have a custom api domain: "https://api.example.com/1.1/"
+    pub fn new(name: &str, owner_id: &str, version: &str, api_url: &str) -> Self {
+        Self::with_protocol(
+            Unsigned,
+            Credential::plain(name),
+            Credential::plain(owner_id),
+            version,
+            api_url,
+        )
+    }
+
+    /// same as new but with the credentials from keyauth_app!, they stay encrypted in memory
+    pub fn from_app(app: AppCredentials, api_url: &str) -> Self {
+        Self::with_protocol(Unsigned, app.name, app.owner_id, &app.version, api_url)
+    }
+}
@@ -0,0 +1,130 @@
+/*!
+unofficial [keyauth](https://keyauth.cc) library that uses 1.3 api version
+
+the 1.3 api signs every response with keyauth's ed25519 key instead of a hmac with the application secret,
+so the secret doesnt have to be in your binary anymore. the signature covers the `x-signature-timestamp` header and the body,
+and responses older than 20 seconds (see `with_max_signature_age`) are rejected so they cant be replayed.
+
+basic usage:
+```rust,ignore
+let mut auth = keyauth::v1_3::KeyauthApi::new("application name", "ownerid", "application version", "api url"); // if you dont have a custom domain for api use "https://keyauth.win/api/1.3/"
+auth.init(None).await.inner().unwrap();
+auth.login("username".to_string(), "password".to_string(), None, None).await.inner().unwrap();
+```
+*/
+
+pub use crate::{KeyauthClient, Res};
+
+use crate::api::Protocol;
+use crate::credentials::{AppCredentials, Credential};
+use crate::transport::{Body, Data, Resp};
+use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// keyauth's public key the 1.3 responses are signed with
+pub const KEYAUTH_PUBLIC_KEY: &str =
+    "5586b4bc69c7a4b487e4563a4cd96afd39140f919bd31cea7d1c6a1e8439422b";
+
+fn parse_public_key(public_key: &str) -> Result<VerifyingKey, String> {
+    let key: [u8; 32] = match hex::decode(public_key).ok().and_then(|k| k.try_into().ok()) {
+        Some(key) => key,
+        None => return Err("public key has to be 32 hex encoded bytes".to_string()),
+    };
+    VerifyingKey::from_bytes(&key).map_err(|e| e.to_string())
+}
+
+/// every function in this struct (accept log and ban) returns a Result and Err("response was tampered with: ...") will be returned
+/// if the ed25519 signature of the response doesnt verify against the public key
+pub type KeyauthApi = crate::api::KeyauthApi<Ed25519>;
+
+/// how the 1.3 api signs its responses: an ed25519 signature over the `x-signature-timestamp` header and the body
+#[derive(Clone)]
+pub struct Ed25519 {
+    public_key: VerifyingKey,
+    /// responses with a signature timestamp further away from the local clock than this are rejected
+    max_signature_age: Duration,
+}
+
+impl Default for Ed25519 {
+    fn default() -> Self {
+        Self {
+            public_key: parse_public_key(KEYAUTH_PUBLIC_KEY).unwrap(),
+            max_signature_age: Duration::from_secs(20),
+        }
+    }
+}
+
+impl Protocol for Ed25519 {
+    type Nonce = ();
+
+    fn encode(&self, data: &Data, _init: bool) -> (Body, ()) {
+        (data.form(), ())
+    }
+
+    /// checks the ed25519 signature over timestamp + body and that the timestamp is recent
+    fn verify(&self, resp: &Resp, _nonce: (), _init: bool) -> Result<String, String> {
+        let signature = resp
+            .head
+            .get("x-signature-ed25519")
+            .and_then(|sig| sig.to_str().ok())
+            .ok_or("missing signature")?;
+        let timestamp = resp
+            .head
+            .get("x-signature-timestamp")
+            .and_then(|ts| ts.to_str().ok())
+            .ok_or("missing timestamp")?;
+
+        let sent: u64 = timestamp.parse().map_err(|_| "invalid timestamp")?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|_| "invalid system time")?
+            .as_secs();
+        if now.abs_diff(sent) > self.max_signature_age.as_secs() {
+            return Err("timestamp is too old".to_string());
+        }
+
+        let signature = hex::decode(signature).map_err(|_| "invalid signature")?;
+        let signature = Signature::from_slice(&signature).map_err(|_| "invalid signature")?;
+        let message = format!("{}{}", timestamp, resp.res);
+        self.public_key
+            .verify(message.as_bytes(), &signature)
+            .map_err(|_| "invalid signature".to_string())?;
+        Ok(resp.res.clone())
+    }
+}
+
+impl KeyauthApi {
+    /// creats a new KeyauthApi and its defaults, api_url has to be api version 1.3 example: "https://keyauth.win/api/1.3/" or if you have a custom api domain: "https://api.example.com/1.3/"
+    pub fn new(name: &str, owner_id: &str, version: &str, api_url: &str) -> Self {
+        Self::with_protocol(
+            Ed25519::default(),
+            Credential::plain(name),
+            Credential::plain(owner_id),
+            version,
+            api_url,
+        )
+    }
+
+    /// same as new but with the credentials from keyauth_app!, they stay encrypted in memory
+    pub fn from_app(app: AppCredentials, api_url: &str) -> Self {
+        Self::with_protocol(
+            Ed25519::default(),
+            app.name,
+            app.owner_id,
+            &app.version,
+            api_url,
+        )
+    }
+
+    /// verifies responses against `public_key` (hex) instead of keyauth's key, for self hosted keyauth instances
+    pub fn with_public_key(mut self, public_key: &str) -> Result<Self, String> {
+        self.protocol.public_key = parse_public_key(public_key)?;
+        Ok(self)
+    }
+
+    /// rejects responses with a signature timestamp further away from the local clock than `max_signature_age`, 20 seconds by default
+    pub fn with_max_signature_age(mut self, max_signature_age: Duration) -> Self {
+        self.protocol.max_signature_age = max_signature_age;
+        self
+    }
+}
@@ -0,0 +1,266 @@
+/*!
+local http server used by the web loader (`web_login` and `button`)
+
+the keyauth web loader page sends requests to a server running on the users machine, by default `http://127.0.0.1:1337`.
+the server only lives as long as the future waiting for a request, so dropping that future (for example with `tokio::select!`)
+cancels it and frees the port. every connection is read in its own task, so a browser that opens a connection and never
+sends on it doesnt hold up the others.
+*/
+
+use std::collections::HashMap;
+use std::fmt;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+const MAX_REQUEST_SIZE: usize = 16 * 1024;
+const MAX_HEADERS: usize = 64;
+/// accept errors in a row before wait_for gives up, errors like running out of file descriptors come back right away
+const MAX_ACCEPT_FAILURES: u32 = 10;
+/// the web loader page is served from keyauth's domain, so the browser needs these to let it call the local server,
+/// `Access-Control-Allow-Private-Network` is required by chromium for requests from a public site to localhost
+const CORS_HEADERS: &str = "Access-Control-Allow-Origin: *\r\nAccess-Control-Allow-Methods: GET, POST, OPTIONS\r\nAccess-Control-Allow-Headers: *\r\nAccess-Control-Allow-Private-Network: true\r\n";
+
+/// where the web loader server listens and how long it waits
+#[derive(Debug, Clone)]
+pub struct WebLoaderConfig {
+    /// address to bind to, the keyauth web loader uses 127.0.0.1:1337
+    pub addr: SocketAddr,
+    /// how long to wait for the web loader before giving up, None waits forever
+    pub timeout: Option<Duration>,
+    /// how long a single connection has to send its request
+    pub read_timeout: Duration,
+}
+
+impl Default for WebLoaderConfig {
+    fn default() -> Self {
+        Self {
+            addr: SocketAddr::from(([127, 0, 0, 1], 1337)),
+            timeout: Some(Duration::from_secs(300)),
+            read_timeout: Duration::from_secs(3),
+        }
+    }
+}
+
+impl WebLoaderConfig {
+    /// same as the default config but listening on `addr`
+    pub fn new(addr: SocketAddr) -> Self {
+        Self {
+            addr,
+            ..Default::default()
+        }
+    }
+}
+
+/// errors returned by web_login
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WebLoginError {
+    /// couldnt bind the local server, contains the io error
+    Bind(String),
+    /// the local server kept failing to accept connections, contains the last io error
+    Accept(String),
+    /// the web loader didnt send a request before the configured timeout
+    Timeout,
+    /// the handshake was missing the user or token
+    InvalidHandshake,
+    /// the response signature didnt match
+    Tampered,
+    /// keyauth couldnt be reached, contains the http error
+    Network(String),
+    /// the response passed the signature check but isnt json, contains the response
+    InvalidResponse(String),
+    /// keyauth rejected the login, contains the message returned by the server
+    Rejected(String),
+}
+
+impl fmt::Display for WebLoginError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WebLoginError::Bind(e) => write!(f, "couldnt bind the web loader server: {}", e),
+            WebLoginError::Accept(e) => {
+                write!(f, "web loader server couldnt accept connections: {}", e)
+            }
+            WebLoginError::Timeout => write!(f, "timed out waiting for the web loader"),
+            WebLoginError::InvalidHandshake => write!(f, "handshake is missing user or token"),
+            WebLoginError::Tampered => write!(f, "response was tampered with"),
+            WebLoginError::Network(e) => write!(f, "{}", e),
+            WebLoginError::InvalidResponse(resp) => write!(f, "invalid response: {}", resp),
+            WebLoginError::Rejected(msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl std::error::Error for WebLoginError {}
+
+/// a parsed request from the web loader, has to be answered with respond
+pub(crate) struct LoaderRequest {
+    pub(crate) method: String,
+    pub(crate) path: String,
+    pub(crate) query: HashMap<String, String>,
+    stream: TcpStream,
+}
+
+impl LoaderRequest {
+    /// sends a http/1.1 response with a plain text body and closes the connection
+    pub(crate) async fn respond(mut self, status: u16, body: &str) -> std::io::Result<()> {
+        let response = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+            status,
+            reason(status),
+            body.len(),
+            CORS_HEADERS,
+            body
+        );
+        self.stream.write_all(response.as_bytes()).await?;
+        self.stream.shutdown().await
+    }
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        _ => "Unknown",
+    }
+}
+
+pub(crate) struct LoaderServer {
+    listener: TcpListener,
+    config: WebLoaderConfig,
+}
+
+impl LoaderServer {
+    pub(crate) async fn bind(config: &WebLoaderConfig) -> Result<Self, WebLoginError> {
+        match TcpListener::bind(config.addr).await {
+            Ok(listener) => Ok(Self {
+                listener,
+                config: config.clone(),
+            }),
+            Err(e) => Err(WebLoginError::Bind(format!("{}: {}", config.addr, e))),
+        }
+    }
+
+    /// waits for a request to `path` (query string excluded) and returns it.
+    /// requests to other paths get a 404, malformed requests a 400, and the wait ends after the configured timeout
+    pub(crate) async fn wait_for(&self, path: &str) -> Result<LoaderRequest, WebLoginError> {
+        let (found, mut requests) = mpsc::channel(1);
+        let accept = async {
+            let mut failures = 0;
+            loop {
+                let stream = match self.listener.accept().await {
+                    Ok((stream, _)) => stream,
+                    Err(e) => {
+                        failures += 1;
+                        if failures >= MAX_ACCEPT_FAILURES {
+                            return WebLoginError::Accept(e.to_string());
+                        }
+                        tokio::time::sleep(Duration::from_millis(50) * failures).await;
+                        continue;
+                    }
+                };
+                failures = 0;
+                tokio::spawn(handle_connection(
+                    stream,
+                    path.to_string(),
+                    self.config.read_timeout,
+                    found.clone(),
+                ));
+            }
+        };
+        let wait = async {
+            tokio::select! {
+                err = accept => Err(err),
+                Some(req) = requests.recv() => Ok(req),
+            }
+        };
+        match self.config.timeout {
+            Some(timeout) => tokio::time::timeout(timeout, wait)
+                .await
+                .unwrap_or(Err(WebLoginError::Timeout)),
+            None => wait.await,
+        }
+    }
+}
+
+/// reads a request within `read_timeout` and sends it to `found` if it is for `path`, answers everything else itself
+async fn handle_connection(
+    stream: TcpStream,
+    path: String,
+    read_timeout: Duration,
+    found: mpsc::Sender<LoaderRequest>,
+) {
+    let req = match tokio::time::timeout(read_timeout, read_request(stream)).await {
+        Ok(Some(req)) => req,
+        _ => return,
+    };
+    if req.method == "OPTIONS" {
+        let _ = req.respond(204, "").await;
+    } else if req.path == path {
+        // fails once wait_for is done, the connection is closed then
+        let _ = found.send(req).await;
+    } else {
+        let _ = req.respond(404, "Not Found").await;
+    }
+}
+
+/// reads and parses a request, answers with 400/431 and returns None if it cant be parsed
+async fn read_request(mut stream: TcpStream) -> Option<LoaderRequest> {
+    let mut buf = Vec::with_capacity(1024);
+    let mut chunk = [0u8; 1024];
+    loop {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+
+        let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+        let mut req = httparse::Request::new(&mut headers);
+        let (method, target) = match req.parse(&buf) {
+            Ok(httparse::Status::Complete(_)) => (
+                req.method.unwrap_or_default().to_string(),
+                req.path.unwrap_or_default().to_string(),
+            ),
+            Ok(httparse::Status::Partial) if buf.len() < MAX_REQUEST_SIZE => continue,
+            Ok(httparse::Status::Partial) | Err(httparse::Error::TooManyHeaders) => {
+                let _ = respond_raw(&mut stream, 431).await;
+                return None;
+            }
+            Err(_) => {
+                let _ = respond_raw(&mut stream, 400).await;
+                return None;
+            }
+        };
+
+        let (path, query) = match target.split_once('?') {
+            Some((path, query)) => (path.to_string(), query.to_string()),
+            None => (target, String::new()),
+        };
+        let query = form_urlencoded::parse(query.as_bytes())
+            .into_owned()
+            .collect();
+        return Some(LoaderRequest {
+            method,
+            path,
+            query,
+            stream,
+        });
+    }
+}
+
+async fn respond_raw(stream: &mut TcpStream, status: u16) -> std::io::Result<()> {
+    let response = format!(
+        "HTTP/1.1 {} {}\r\nContent-Length: 0\r\n{}Connection: close\r\n\r\n",
+        status,
+        reason(status),
+        CORS_HEADERS
+    );
+    stream.write_all(response.as_bytes()).await
+}
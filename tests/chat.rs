@@ -0,0 +1,96 @@
+use keyauth_obf::chat::ChatError;
+use std::time::Duration;
+
+#[test]
+fn server_messages_map_to_typed_errors() {
+    assert_eq!(
+        ChatError::from("You're muted from chat until 1714568400".to_string()),
+        ChatError::Muted {
+            until: Some(1714568400)
+        }
+    );
+    assert_eq!(
+        ChatError::from("You are muted until 2024-05-01 13:00:00.".to_string()),
+        ChatError::Muted {
+            until: Some(1714568400)
+        }
+    );
+    assert_eq!(
+        ChatError::from("Muted Until 05/01/2024 13:00".to_string()),
+        ChatError::Muted {
+            until: Some(1714568400)
+        }
+    );
+    assert_eq!(
+        ChatError::from("You're muted".to_string()),
+        ChatError::Muted { until: None }
+    );
+    assert_eq!(
+        ChatError::from("Message too long".to_string()),
+        ChatError::MessageTooLong
+    );
+    assert_eq!(
+        ChatError::from("Channel doesn't exist".to_string()),
+        ChatError::ChannelNotFound
+    );
+    assert_eq!(
+        ChatError::from("Chat delay limit reached".to_string()),
+        ChatError::RateLimited { retry_after: None }
+    );
+    assert_eq!(
+        ChatError::from("Session not found".to_string()),
+        ChatError::Other("Session not found".to_string())
+    );
+}
+
+#[test]
+fn out_of_range_dates_dont_panic() {
+    assert_eq!(
+        ChatError::from("muted until 99999999999999999-01-01".to_string()),
+        ChatError::Muted { until: None }
+    );
+    assert_eq!(
+        ChatError::from("muted until 2024-05-01 99999999999999999:00".to_string()),
+        ChatError::Muted { until: None }
+    );
+}
+
+#[test]
+fn non_ascii_messages_dont_panic() {
+    // lowercasing "İ" makes the string longer, an index found in the lowercased text would be off
+    assert_eq!(
+        ChatError::from("İİİ muted UNTIL İ".to_string()),
+        ChatError::Muted { until: None }
+    );
+    assert_eq!(
+        ChatError::from("İİİİİİ muted until 1714568400".to_string()),
+        ChatError::Muted {
+            until: Some(1714568400)
+        }
+    );
+    assert_eq!(
+        ChatError::from("ẞ ist zu lang ✉".to_string()),
+        ChatError::Other("ẞ ist zu lang ✉".to_string())
+    );
+}
+
+#[test]
+fn retry_after_is_only_added_to_rate_limits() {
+    let limited = ChatError::RateLimited { retry_after: None };
+    assert_eq!(
+        limited.clone().with_retry_after(Some(" 30 ")),
+        ChatError::RateLimited {
+            retry_after: Some(Duration::from_secs(30))
+        }
+    );
+    assert_eq!(limited.clone().with_retry_after(Some("soon")), limited);
+    assert_eq!(limited.clone().with_retry_after(None), limited);
+    assert_eq!(
+        ChatError::MessageTooLong.with_retry_after(Some("30")),
+        ChatError::MessageTooLong
+    );
+    let known = ChatError::RateLimited {
+        retry_after: Some(Duration::from_secs(5)),
+    };
+    assert_eq!(known.clone().with_retry_after(Some("30")), known);
+}
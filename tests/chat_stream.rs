@@ -0,0 +1,132 @@
+#![cfg(feature = "v1_2")]
+
+mod common;
+
+use common::{init_response, mock_api, Params};
+use futures_util::StreamExt;
+use keyauth_obf::chat::{ChatError, ChatMessage};
+use keyauth_obf::v1_2::KeyauthApi;
+use serde_json::{json, Value};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+fn message(author: &str, text: &str, timestamp: u64) -> Value {
+    json!({ "author": author, "message": text, "timestamp": timestamp.to_string() })
+}
+
+fn chat(messages: Value) -> Value {
+    json!({ "success": true, "message": "Successfully retrieved chat messages", "messages": messages })
+}
+
+async fn client(url: &str) -> KeyauthApi {
+    let mut auth = KeyauthApi::new("app", "owner", "secret", "1.0", url);
+    auth.init(None).await.inner().unwrap();
+    auth
+}
+
+#[tokio::test]
+async fn messages_are_parsed() {
+    let mock = mock_api("secret", |params: &Params| {
+        match (
+            params["type"].as_str(),
+            params.get("channel").map(String::as_str),
+        ) {
+            ("init", _) => init_response(),
+            ("chatget", Some("general")) => chat(json!([
+                { "author": "alice", "message": "hi", "timestamp": 1660000000 },
+                { "author": "bob", "message": "hey", "timestamp": " 1660000001 " }
+            ])),
+            ("chatget", Some("empty")) => chat(Value::Null),
+            ("chatget", Some("broken")) => chat(json!([
+                { "author": "alice", "message": "hi", "timestamp": "yesterday" }
+            ])),
+            _ => json!({ "success": false, "message": "Channel doesn't exist" }),
+        }
+    })
+    .await;
+    let auth = client(&mock.url).await;
+
+    let messages = auth
+        .get_chat_messages("general".to_string())
+        .await
+        .inner()
+        .unwrap();
+    assert_eq!(
+        messages,
+        [
+            ChatMessage {
+                author: "alice".to_string(),
+                message: "hi".to_string(),
+                timestamp: 1660000000
+            },
+            ChatMessage {
+                author: "bob".to_string(),
+                message: "hey".to_string(),
+                timestamp: 1660000001
+            }
+        ]
+    );
+    assert_eq!(
+        auth.get_chat_messages("empty".to_string()).await.inner(),
+        Ok(Vec::new())
+    );
+    assert!(matches!(
+        auth.get_chat_messages("broken".to_string()).await.inner(),
+        Err(ChatError::Other(_))
+    ));
+    assert_eq!(
+        auth.get_chat_messages("missing".to_string()).await.inner(),
+        Err(ChatError::ChannelNotFound)
+    );
+}
+
+#[tokio::test]
+async fn stream_yields_each_message_once_and_keeps_polling_after_errors() {
+    let polls = AtomicUsize::new(0);
+    let mock = mock_api("secret", move |params: &Params| {
+        match params["type"].as_str() {
+            "init" => init_response(),
+            "chatget" => match polls.fetch_add(1, Ordering::SeqCst) {
+                0 => chat(json!([message("alice", "hi", 1), message("bob", "hey", 2)])),
+                1 => chat(json!([message("alice", "hi", 1), message("bob", "hey", 2)])),
+                2 => json!({ "success": false, "message": "Chat delay limit reached" }),
+                // the oldest message scrolled out and the same text was sent again later
+                _ => chat(json!([
+                    message("bob", "hey", 2),
+                    message("alice", "hi", 3),
+                    message("carol", "yo", 4)
+                ])),
+            },
+            _ => json!({ "success": false, "message": "Unhandled" }),
+        }
+    })
+    .await;
+    let auth = client(&mock.url).await;
+
+    let items: Vec<Result<ChatMessage, ChatError>> = auth
+        .chat_stream("general".to_string(), Duration::from_millis(10))
+        .take(5)
+        .map(|item| item.inner())
+        .collect()
+        .await;
+    let texts: Vec<Result<(String, u64), ChatError>> = items
+        .into_iter()
+        .map(|item| item.map(|m| (m.author, m.timestamp)))
+        .collect();
+    assert_eq!(
+        texts,
+        [
+            Ok(("alice".to_string(), 1)),
+            Ok(("bob".to_string(), 2)),
+            Err(ChatError::RateLimited { retry_after: None }),
+            Ok(("alice".to_string(), 3)),
+            Ok(("carol".to_string(), 4)),
+        ]
+    );
+    let polled = mock
+        .requests()
+        .iter()
+        .filter(|p| p["type"] == "chatget")
+        .count();
+    assert_eq!(polled, 4);
+}
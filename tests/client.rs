@@ -0,0 +1,242 @@
+#![cfg(all(feature = "v1_1", feature = "v1_2"))]
+
+mod common;
+
+use common::{free_addr, mock_api, Params};
+use futures_util::StreamExt;
+use keyauth_obf::chat::ChatMessage;
+use keyauth_obf::{v1_1, v1_2, KeyauthClient, KeyauthHandle};
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+
+fn api(params: &Params) -> Value {
+    match params["type"].as_str() {
+        "init" => json!({
+            "success": true,
+            "message": "Initialized",
+            "sessionid": "session",
+            "appinfo": { "numKeys": "1", "numOnlineUsers": "2", "numUsers": "1" }
+        }),
+        "login" => json!({
+            "success": true,
+            "message": "Logged in!",
+            "info": {
+                "ip": "127.0.0.1",
+                "createdate": "1660000000",
+                "lastlogin": "1660000001",
+                "subscriptions": [{ "subscription": "default" }]
+            }
+        }),
+        "chatget" => json!({
+            "success": true,
+            "message": "Successfully retrieved chat messages",
+            "messages": [{ "author": "alice", "message": "hi", "timestamp": "1660000002" }]
+        }),
+        "fetchOnline" => json!({
+            "success": true,
+            "message": "Successfully fetched online users",
+            "users": [{ "credential": "alice" }, { "credential": "bob" }]
+        }),
+        "fetchStats" => json!({
+            "success": true,
+            "message": "Successfully fetched stats",
+            "appinfo": {
+                "numUsers": "10",
+                "numOnlineUsers": "3",
+                "numKeys": "25",
+                "version": "1.1",
+                "customerPanelLink": "https://keyauth.cc/panel/owner/app/"
+            }
+        }),
+        "forgot" => {
+            json!({ "success": true, "message": "Successfully sent email to change password." })
+        }
+        "var" => {
+            // slow enough that requests queued behind each other would be noticed
+            std::thread::sleep(Duration::from_millis(300));
+            json!({ "success": true, "message": params["varid"] })
+        }
+        "logout" => json!({ "success": true, "message": "Logged out" }),
+        "checkblacklist" => json!({ "success": true, "message": "Client is blacklisted" }),
+        "setvar" => json!({ "success": true, "message": "Successfully set variable" }),
+        _ => json!({ "success": false, "message": "Unhandled" }),
+    }
+}
+
+/// the same launcher code for every api version
+async fn launch(auth: &mut dyn KeyauthClient) -> (String, ChatMessage, usize) {
+    assert_eq!(auth.init(None).await.inner().unwrap().num_online_users, 2);
+    let session = auth
+        .login("bob".to_string(), "password".to_string(), None, None)
+        .await
+        .inner()
+        .unwrap();
+    assert_eq!(session.last_login, 1660000001);
+    assert_eq!(session.subscriptions[0].subscription, "default");
+    let message = auth
+        .chat_stream("general".to_string(), Duration::from_secs(60))
+        .next()
+        .await
+        .unwrap()
+        .inner()
+        .unwrap();
+    let mut tracker = auth.presence_tracker();
+    assert_eq!(tracker.count(), 2);
+    auth.poll_presence(&mut tracker).await.inner().unwrap();
+    (auth.subscription().to_string(), message, tracker.count())
+}
+
+#[tokio::test]
+async fn api_versions_are_interchangeable() {
+    let mock = mock_api("secret", api).await;
+    let clients: Vec<Box<dyn KeyauthClient>> = vec![
+        Box::new(v1_1::KeyauthApi::new("app", "owner", "1.0", &mock.url)),
+        Box::new(v1_2::KeyauthApi::new(
+            "app", "owner", "secret", "1.0", &mock.url,
+        )),
+    ];
+
+    for mut auth in clients {
+        let (subscription, message, online) = launch(auth.as_mut()).await;
+        assert_eq!(subscription, "default");
+        assert_eq!(message.author, "alice");
+        assert_eq!(message.timestamp, 1660000002);
+        assert_eq!(online, 2);
+        assert_eq!(auth.username(), "bob");
+    }
+    assert_eq!(mock.requests().len(), 8);
+}
+
+#[tokio::test]
+async fn stats_forgot_password_and_logout() {
+    let mock = mock_api("secret", api).await;
+    let mut auth = v1_2::KeyauthApi::new("app", "owner", "secret", "1.0", &mock.url);
+    auth.init(None).await.inner().unwrap();
+
+    let app = auth.fetch_stats().await.inner().unwrap();
+    assert_eq!(app.num_users, 10);
+    assert_eq!(app.version, "1.1");
+    assert_eq!(auth.num_users, "10");
+    assert_eq!(auth.num_online_users, "3");
+    assert_eq!(auth.num_keys, "25");
+    assert_eq!(auth.app_version, "1.1");
+    assert_eq!(auth.presence_tracker().count(), 3);
+
+    assert_eq!(
+        auth.forgot_password("bob".to_string(), "bob@example.com".to_string())
+            .await
+            .inner(),
+        Ok("Successfully sent email to change password.".to_string())
+    );
+    let forgot = mock.last("forgot").unwrap();
+    assert_eq!(forgot["username"], "bob");
+    assert_eq!(forgot["email"], "bob@example.com");
+
+    auth.login("bob".to_string(), "password".to_string(), None, None)
+        .await
+        .inner()
+        .unwrap();
+    assert_eq!(auth.user().unwrap().create_date, 1660000000);
+    assert_eq!(auth.username(), "bob");
+    auth.logout().await.inner().unwrap();
+    assert_eq!(auth.user(), None);
+    assert_eq!(auth.username(), "");
+    assert_eq!(auth.subscription(), "");
+    assert_eq!(auth.ip, "");
+    assert_eq!(auth.create_date, "");
+    assert_eq!(mock.last("logout").unwrap()["sessionid"], "session");
+}
+
+#[tokio::test]
+async fn stats_forgot_password_and_logout_return_errors() {
+    let mock = mock_api("secret", |params: &Params| match params["type"].as_str() {
+        "fetchStats" => json!("not json"),
+        _ => api(params),
+    })
+    .await;
+    let mut auth = v1_2::KeyauthApi::new("app", "owner", "secret", "1.0", &mock.url);
+    auth.init(None).await.inner().unwrap();
+    assert!(auth.fetch_stats().await.inner().is_err());
+
+    // keyauth cant be reached anymore
+    auth.api_url = format!("http://{}/", free_addr());
+    assert!(auth.fetch_stats().await.inner().is_err());
+    assert!(auth
+        .forgot_password("bob".to_string(), "bob@example.com".to_string())
+        .await
+        .inner()
+        .is_err());
+    assert!(auth.logout().await.inner().is_err());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+async fn handle_is_shared_between_tasks() {
+    let mock = mock_api("secret", api).await;
+    let auth = KeyauthHandle::new(v1_2::KeyauthApi::new(
+        "app", "owner", "secret", "1.0", &mock.url,
+    ));
+    auth.init(None).await.inner().unwrap();
+    auth.login("bob".to_string(), "password".to_string(), None, None)
+        .await
+        .inner()
+        .unwrap();
+
+    let start = Instant::now();
+    let tasks: Vec<_> = (0..4)
+        .map(|i| {
+            let auth = auth.clone();
+            tokio::spawn(async move { auth.var(format!("var{}", i)).await.inner() })
+        })
+        .collect();
+    for (i, task) in tasks.into_iter().enumerate() {
+        assert_eq!(task.await.unwrap(), Ok(format!("var{}", i)));
+    }
+    assert!(start.elapsed() < Duration::from_millis(1000));
+
+    assert_eq!(auth.read().await.username, "bob");
+    let var = mock.last("var").unwrap();
+    assert_eq!(var["sessionid"], "session");
+
+    // calls that change the client keep their changes
+    auth.checkblacklist().await.inner().unwrap();
+    auth.setvar("var".to_string(), "value".to_string())
+        .await
+        .inner()
+        .unwrap();
+    let client = auth.read().await;
+    assert!(client.blacklisted);
+    assert_eq!(client.message, "Successfully set variable");
+}
+
+#[tokio::test]
+async fn init_with_self_hash_sends_the_md5_of_the_executable() {
+    let mock = mock_api("secret", api).await;
+    let mut auth = v1_1::KeyauthApi::new("app", "owner", "1.0", &mock.url);
+    auth.init_with_self_hash().await.inner().unwrap();
+
+    let exe = std::env::current_exe().unwrap();
+    let expected = format!("{:x}", md5::compute(std::fs::read(&exe).unwrap()));
+    assert_eq!(mock.last("init").unwrap()["hash"], expected);
+    assert_eq!(keyauth_obf::integrity::file_hash(&exe).unwrap(), expected);
+}
+
+#[tokio::test]
+async fn online_count_is_the_number_of_sessions() {
+    let mock = mock_api("secret", |params: &Params| match params["type"].as_str() {
+        "fetchOnline" => json!({
+            "success": true,
+            "message": "Successfully fetched online users",
+            "users": [{ "credential": "alice" }, { "credential": "alice" }, { "credential": "bob" }]
+        }),
+        _ => api(params),
+    })
+    .await;
+    let mut auth = v1_2::KeyauthApi::new("app", "owner", "secret", "1.0", &mock.url);
+    auth.init(None).await.inner().unwrap();
+
+    let mut tracker = auth.presence_tracker();
+    auth.poll_presence(&mut tracker).await.inner().unwrap();
+    assert_eq!(auth.num_online_users, "3");
+    assert_eq!(tracker.count(), 3);
+    assert_eq!(tracker.users().len(), 2);
+}
@@ -0,0 +1,174 @@
+//! a local stand-in for the keyauth api, answers every request with the json returned by a handler
+//! (or the raw text if the handler returns a json string) and signs it the same way keyauth does.
+//! mock_raw leaves the encoding and signing to the handler
+
+#![allow(dead_code)]
+
+use hmac_sha256::HMAC;
+use reqwest::Url;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+pub type Params = HashMap<String, String>;
+
+pub struct MockApi {
+    pub url: String,
+    requests: Arc<Mutex<Vec<Params>>>,
+    bodies: Arc<Mutex<Vec<String>>>,
+}
+
+impl MockApi {
+    /// every request received so far, query string and form body merged
+    pub fn requests(&self) -> Vec<Params> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    /// the raw body of every request received so far
+    pub fn bodies(&self) -> Vec<String> {
+        self.bodies.lock().unwrap().clone()
+    }
+
+    /// the last request with `type` set to `ty`
+    pub fn last(&self, ty: &str) -> Option<Params> {
+        self.requests()
+            .into_iter()
+            .rev()
+            .find(|p| p.get("type").map(String::as_str) == Some(ty))
+    }
+}
+
+/// starts the mock on a random port, `secret` is the application secret used for the signatures
+pub async fn mock_api<F>(secret: &str, handler: F) -> MockApi
+where
+    F: Fn(&Params) -> serde_json::Value + Send + Sync + 'static,
+{
+    let secret = secret.to_string();
+    let enckey = Mutex::new(String::new());
+    mock_raw(move |params| {
+        let key = if params.get("type").map(String::as_str) == Some("init") {
+            *enckey.lock().unwrap() = params.get("enckey").cloned().unwrap_or_default();
+            secret.clone()
+        } else {
+            format!("{}-{}", enckey.lock().unwrap(), secret)
+        };
+        let body = match handler(params) {
+            serde_json::Value::String(text) => text,
+            json => json.to_string(),
+        };
+        let signature = hex::encode(HMAC::mac(body.as_bytes(), key.as_bytes()));
+        (vec![("signature".to_string(), signature)], body)
+    })
+    .await
+}
+
+/// starts a mock that answers with the headers and body returned by `handler`, for the api versions that encode or
+/// sign their responses differently from 1.2 (1.0, 1.1 and 1.3)
+pub async fn mock_raw<F>(handler: F) -> MockApi
+where
+    F: Fn(&Params) -> (Vec<(String, String)>, String) + Send + Sync + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+    let bodies = Arc::new(Mutex::new(Vec::new()));
+    let handler = Arc::new(handler);
+
+    let (reqs, bods) = (requests.clone(), bodies.clone());
+    tokio::spawn(async move {
+        loop {
+            let (stream, _) = match listener.accept().await {
+                Ok(conn) => conn,
+                Err(_) => continue,
+            };
+            let (reqs, bods, handler) = (reqs.clone(), bods.clone(), handler.clone());
+            tokio::spawn(async move {
+                let (mut stream, params, body) = match read_params(stream).await {
+                    Some(req) => req,
+                    None => return,
+                };
+                reqs.lock().unwrap().push(params.clone());
+                bods.lock().unwrap().push(body);
+                let (headers, body) = handler(&params);
+                let headers: String = headers
+                    .iter()
+                    .map(|(name, value)| format!("{}: {}\r\n", name, value))
+                    .collect();
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    headers,
+                    body.len(),
+                    body
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+                let _ = stream.shutdown().await;
+            });
+        }
+    });
+
+    MockApi {
+        url,
+        requests,
+        bodies,
+    }
+}
+
+/// a successful init with a small app
+pub fn init_response() -> serde_json::Value {
+    serde_json::json!({
+        "success": true,
+        "message": "Initialized",
+        "sessionid": "session",
+        "appinfo": { "numKeys": "1", "numOnlineUsers": "0", "numUsers": "1" }
+    })
+}
+
+/// a local address nothing is listening on
+pub fn free_addr() -> SocketAddr {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+}
+
+async fn read_params(mut stream: TcpStream) -> Option<(TcpStream, Params, String)> {
+    let mut buf = Vec::new();
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            return None;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+        if let Some(i) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break i + 4;
+        }
+    };
+    let head = String::from_utf8_lossy(&buf[..head_end]).to_string();
+    let content_length = head
+        .lines()
+        .find_map(|l| {
+            let (name, value) = l.split_once(':')?;
+            name.eq_ignore_ascii_case("content-length")
+                .then(|| value.trim().parse::<usize>().ok())?
+        })
+        .unwrap_or(0);
+    while buf.len() < head_end + content_length {
+        let n = stream.read(&mut chunk).await.ok()?;
+        if n == 0 {
+            break;
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    }
+    let target = head.split_whitespace().nth(1)?;
+    let body = String::from_utf8_lossy(&buf[head_end..]).to_string();
+
+    let mut params = Params::new();
+    let url = Url::parse(&format!("http://localhost{}", target)).ok()?;
+    params.extend(url.query_pairs().into_owned());
+    let form = Url::parse(&format!("http://localhost/?{}", body)).ok()?;
+    params.extend(form.query_pairs().into_owned());
+    Some((stream, params, body))
+}
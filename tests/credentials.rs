@@ -0,0 +1,60 @@
+#![cfg(feature = "v1_2")]
+
+mod common;
+
+use common::{init_response, mock_api};
+use keyauth_obf::keyauth_app;
+use keyauth_obf::v1_2::KeyauthApi;
+
+// built at runtime so the plaintext only exists in the keyauth_app! call below
+fn reversed(s: &str) -> String {
+    s.chars().rev().collect()
+}
+
+#[tokio::test]
+async fn credentials_are_encrypted_in_the_binary() {
+    let secret = reversed("7de4-terces-ppa-tset-htuayek");
+    let owner_id = reversed("9z8y7x-direnwo");
+    let mock = mock_api(&secret, |_| init_response()).await;
+
+    let app = keyauth_app! {
+        name: "app",
+        owner_id: "ownerid-x7y8z9",
+        secret: "keyauth-test-app-secret-4ed7",
+        version: "1.0",
+    };
+    assert_eq!(format!("{:?}", app.secret), "Credential(..)");
+    let mut auth = KeyauthApi::from_app(app, &mock.url);
+    // init only succeeds if the signature made with the decrypted secret matches
+    auth.init(None).await.inner().unwrap();
+    let init = mock.last("init").unwrap();
+    assert_eq!(init["ownerid"], owner_id);
+    assert_eq!(init["name"], "app");
+    assert_eq!(init["ver"], "1.0");
+
+    let binary = std::fs::read(std::env::current_exe().unwrap()).unwrap();
+    for plaintext in [&secret, &owner_id] {
+        assert!(!binary
+            .windows(plaintext.len())
+            .any(|w| w == plaintext.as_bytes()));
+    }
+}
+
+#[tokio::test]
+async fn clones_keep_working_after_the_original_is_dropped() {
+    let mock = mock_api("secret", |_| init_response()).await;
+    let mut auth = KeyauthApi::new("app", "owner", "secret", "1.0", &mock.url);
+    auth.init(None).await.inner().unwrap();
+
+    // the clone shares the secret and enckey with the original, dropping it mustnt wipe them
+    let mut clone = auth.clone();
+    drop(auth);
+    clone
+        .login("bob".to_string(), "hunter2".to_string(), None, None)
+        .await
+        .inner()
+        .unwrap();
+    let login = mock.last("login").unwrap();
+    assert_eq!(login["pass"], "hunter2");
+    assert_eq!(login["ownerid"], "owner");
+}
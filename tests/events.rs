@@ -0,0 +1,82 @@
+#![cfg(feature = "v1_2")]
+
+mod common;
+
+use common::{init_response, mock_api, Params};
+use keyauth_obf::events::AuthEvent;
+use keyauth_obf::v1_2::KeyauthApi;
+use serde_json::{json, Value};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+fn api(params: &Params) -> Value {
+    match params["type"].as_str() {
+        "init" => init_response(),
+        "license" => json!({
+            "success": true,
+            "message": "Logged in!",
+            "info": {
+                "username": "bob",
+                "subscriptions": [
+                    { "subscription": "trial", "expiry": (now() + 3600).to_string() },
+                    { "subscription": "default", "expiry": (now() + 30 * 86400).to_string() }
+                ]
+            }
+        }),
+        "checkblacklist" => json!({ "success": true, "message": "Client is blacklisted" }),
+        "check" => json!({ "success": false, "message": "Session not found" }),
+        _ => json!({ "success": false, "message": "Unhandled" }),
+    }
+}
+
+fn recorder(auth: &mut KeyauthApi) -> Arc<Mutex<Vec<AuthEvent>>> {
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let sink = events.clone();
+    auth.on_event(move |event| sink.lock().unwrap().push(event.clone()));
+    events
+}
+
+#[tokio::test]
+async fn hooks_get_the_lifecycle_events() {
+    let mock = mock_api("secret", api).await;
+    let mut auth = KeyauthApi::new("app", "owner", "secret", "1.0", &mock.url);
+    let events = recorder(&mut auth);
+
+    let app = auth.init(None).await.inner().unwrap();
+    let session = auth
+        .license("KEY-1234".to_string(), None)
+        .await
+        .inner()
+        .unwrap();
+    assert!(!auth.check_session().await.inner().unwrap());
+    auth.checkblacklist().await.inner().unwrap();
+
+    assert_eq!(
+        *events.lock().unwrap(),
+        [
+            AuthEvent::Initialized(app),
+            AuthEvent::LoggedIn(session.clone()),
+            AuthEvent::SubscriptionExpiring(session.subscriptions[0].clone()),
+            AuthEvent::LicenseActivated,
+            AuthEvent::SessionExpired,
+            AuthEvent::Blacklisted,
+        ]
+    );
+}
+
+#[tokio::test]
+async fn signature_failures_are_reported() {
+    let mock = mock_api("another secret", api).await;
+    let mut auth = KeyauthApi::new("app", "owner", "secret", "1.0", &mock.url);
+    let events = recorder(&mut auth);
+
+    assert!(auth.init(None).await.inner().is_err());
+    assert_eq!(*events.lock().unwrap(), [AuthEvent::Tampered]);
+}
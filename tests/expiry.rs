@@ -0,0 +1,164 @@
+#![cfg(feature = "v1_2")]
+
+mod common;
+
+use common::{free_addr, init_response, mock_api, Params};
+use keyauth_obf::expiry::{ExpiryConfig, ExpiryEvent, ExpiryWatcher};
+use keyauth_obf::session::{Subscription, UserSession};
+use keyauth_obf::v1_2::KeyauthApi;
+use keyauth_obf::KeyauthHandle;
+use serde_json::{json, Value};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+fn api(params: &Params, timeleft: u64, expiry: u64, active: bool) -> Value {
+    match params["type"].as_str() {
+        "init" => init_response(),
+        "license" => json!({
+            "success": true,
+            "message": "Logged in!",
+            "info": {
+                "username": "bob",
+                "subscriptions": [{
+                    "subscription": "default",
+                    "expiry": expiry.to_string(),
+                    "timeleft": timeleft.to_string()
+                }]
+            }
+        }),
+        "check" => json!({ "success": active, "message": "Session status" }),
+        _ => json!({ "success": false, "message": "Unhandled" }),
+    }
+}
+
+#[tokio::test]
+async fn warns_at_the_thresholds_and_expires() {
+    // timeleft is the earlier one, expiry only has whole seconds and is counted from before init and license
+    let expiry = now() + 4;
+    let mock = mock_api("secret", move |params| api(params, 2, expiry, true)).await;
+    let mut auth = KeyauthApi::new("app", "owner", "secret", "1.0", &mock.url);
+    auth.init(None).await.inner().unwrap();
+    let session = auth.license("KEY".to_string(), None).await.inner().unwrap();
+
+    let start = Instant::now();
+    let mut watcher = ExpiryWatcher::new(
+        &session,
+        ExpiryConfig {
+            thresholds: vec![Duration::from_secs(1)],
+            ..Default::default()
+        },
+    );
+    assert_eq!(
+        watcher.next(&auth).await,
+        Some(ExpiryEvent::Warning {
+            subscription: session.subscriptions[0].clone(),
+            threshold: Duration::from_secs(1),
+        })
+    );
+    assert!(start.elapsed() >= Duration::from_millis(900));
+    assert_eq!(
+        watcher.next(&auth).await,
+        Some(ExpiryEvent::Expired(session.subscriptions[0].clone()))
+    );
+    assert!(start.elapsed() >= Duration::from_millis(1900));
+    assert_eq!(watcher.next(&auth).await, None);
+    // the server was asked before each event
+    let checks = mock
+        .requests()
+        .iter()
+        .filter(|p| p["type"] == "check")
+        .count();
+    assert_eq!(checks, 2);
+}
+
+#[tokio::test]
+async fn clock_tampering_and_ended_sessions_are_reported() {
+    // the server says an hour is left but the local clock says ten days
+    let expiry = now() + 10 * 24 * 60 * 60;
+    let mock = mock_api("secret", move |params| api(params, 3600, expiry, false)).await;
+    let mut auth = KeyauthApi::new("app", "owner", "secret", "1.0", &mock.url);
+    auth.init(None).await.inner().unwrap();
+    let session = auth.license("KEY".to_string(), None).await.inner().unwrap();
+
+    let mut watcher = ExpiryWatcher::new(&session, ExpiryConfig::default());
+    match watcher.next(&auth).await {
+        Some(ExpiryEvent::ClockTampered { drift }) => {
+            assert!(drift > Duration::from_secs(9 * 24 * 60 * 60))
+        }
+        other => panic!("expected ClockTampered, got {:?}", other),
+    }
+    // the 1h warning is due right away, the check before it finds the session gone
+    assert_eq!(watcher.next(&auth).await, Some(ExpiryEvent::SessionEnded));
+    assert_eq!(watcher.next(&auth).await, None);
+}
+
+#[tokio::test]
+async fn keeps_counting_offline_and_skips_subscriptions_without_expiry() {
+    let auth = KeyauthApi::new(
+        "app",
+        "owner",
+        "secret",
+        "1.0",
+        &format!("http://{}/", free_addr()),
+    );
+    let lifetime = Subscription {
+        subscription: "lifetime".to_string(),
+        ..Default::default()
+    };
+    let trial = Subscription {
+        subscription: "trial".to_string(),
+        timeleft: 1,
+        ..Default::default()
+    };
+    let session = UserSession {
+        subscriptions: vec![lifetime, trial.clone()],
+        ..Default::default()
+    };
+
+    let mut watcher = ExpiryWatcher::new(
+        &session,
+        ExpiryConfig {
+            thresholds: Vec::new(),
+            ..Default::default()
+        },
+    );
+    assert_eq!(watcher.subscriptions(), [&trial]);
+    // check_session fails, the subscription still expires
+    assert_eq!(watcher.next(&auth).await, Some(ExpiryEvent::Expired(trial)));
+    assert_eq!(watcher.next(&auth).await, None);
+}
+
+#[tokio::test]
+async fn a_shared_client_isnt_locked_while_waiting() {
+    let expiry = now() + 10;
+    let mock = mock_api("secret", move |params| api(params, 1, expiry, true)).await;
+    let auth = KeyauthHandle::new(KeyauthApi::new("app", "owner", "secret", "1.0", &mock.url));
+    auth.init(None).await.inner().unwrap();
+    let session = auth.license("KEY".to_string(), None).await.inner().unwrap();
+
+    let mut watcher = ExpiryWatcher::new(
+        &session,
+        ExpiryConfig {
+            thresholds: Vec::new(),
+            ..Default::default()
+        },
+    );
+    let shared = auth.clone();
+    let watching = tokio::spawn(async move { watcher.next_shared(&shared).await });
+    // init takes the write lock, it would wait for the watcher if it kept the read lock
+    tokio::time::timeout(Duration::from_millis(500), auth.init(None))
+        .await
+        .unwrap()
+        .inner()
+        .unwrap();
+    assert_eq!(
+        watching.await.unwrap(),
+        Some(ExpiryEvent::Expired(session.subscriptions[0].clone()))
+    );
+}
@@ -0,0 +1,174 @@
+#![cfg(all(feature = "v1_2", feature = "logger"))]
+
+mod common;
+
+use common::{init_response, mock_api, Params};
+use keyauth_obf::logger::{LoggerConfig, RemoteLogger};
+use keyauth_obf::v1_2::KeyauthApi;
+use keyauth_obf::KeyauthHandle;
+use serde_json::{json, Value};
+use std::time::{Duration, Instant};
+
+fn api(params: &Params) -> Value {
+    match params["type"].as_str() {
+        "init" => init_response(),
+        "log" if params["message"] == "too long" => {
+            json!({ "success": false, "message": "Message too long" })
+        }
+        "log" => json!({ "success": true, "message": "Logged" }),
+        _ => json!({ "success": false, "message": "Unhandled" }),
+    }
+}
+
+async fn handle(url: &str) -> KeyauthHandle<KeyauthApi> {
+    let auth = KeyauthHandle::new(KeyauthApi::new("app", "owner", "secret", "1.0", url));
+    auth.init(None).await.inner().unwrap();
+    auth
+}
+
+fn logged(mock: &common::MockApi) -> Vec<String> {
+    mock.requests()
+        .into_iter()
+        .filter(|p| p["type"] == "log")
+        .map(|p| p["message"].clone())
+        .collect()
+}
+
+async fn wait_until_sent(logger: &RemoteLogger) {
+    let start = Instant::now();
+    while logger.pending() > 0 {
+        assert!(
+            start.elapsed() < Duration::from_secs(10),
+            "messages werent sent"
+        );
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+}
+
+#[tokio::test]
+async fn messages_are_sent_in_order_and_rate_limited() {
+    let mock = mock_api("secret", api).await;
+    let logger = RemoteLogger::start(
+        handle(&mock.url).await,
+        LoggerConfig {
+            max_per_window: 2,
+            window: Duration::from_millis(500),
+            pcuser: Some("launcher".to_string()),
+            ..Default::default()
+        },
+    );
+
+    let start = Instant::now();
+    for i in 0..5 {
+        logger.log(format!("message {}", i));
+    }
+    wait_until_sent(&logger).await;
+    // 2 right away, 2 after one window and the last after two
+    assert!(start.elapsed() >= Duration::from_millis(1000));
+    logger.shutdown().await;
+
+    assert_eq!(
+        logged(&mock),
+        (0..5).map(|i| format!("message {}", i)).collect::<Vec<_>>()
+    );
+    assert_eq!(mock.last("log").unwrap()["pcuser"], "launcher");
+}
+
+#[tokio::test]
+async fn queue_is_kept_on_disk_while_offline() {
+    let queue_file = std::env::temp_dir().join(format!("keyauth-logs-{}.json", std::process::id()));
+    let config = LoggerConfig {
+        queue_file: Some(queue_file.clone()),
+        retry_after: Duration::from_secs(60),
+        ..Default::default()
+    };
+
+    // nothing listens on the port anymore
+    let offline_url = {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        format!("http://{}/", listener.local_addr().unwrap())
+    };
+    let offline = KeyauthHandle::new(KeyauthApi::new(
+        "app",
+        "owner",
+        "secret",
+        "1.0",
+        &offline_url,
+    ));
+    let logger = RemoteLogger::start(offline, config.clone());
+    logger.log("first");
+    logger.log("second");
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    logger.shutdown().await;
+    let saved: Vec<String> = serde_json::from_slice(&std::fs::read(&queue_file).unwrap()).unwrap();
+    assert_eq!(saved, ["first", "second"]);
+
+    let mock = mock_api("secret", api).await;
+    let logger = RemoteLogger::start(handle(&mock.url).await, config);
+    logger.log("third");
+    wait_until_sent(&logger).await;
+    logger.shutdown().await;
+    assert_eq!(logged(&mock), ["first", "second", "third"]);
+    assert!(!queue_file.exists());
+}
+
+#[tokio::test]
+async fn new_messages_dont_retry_before_retry_after() {
+    // the log answers arent json, so the messages arent sent
+    let mock = mock_api("secret", |params| match params["type"].as_str() {
+        "init" => init_response(),
+        _ => json!("<html>502 Bad Gateway</html>"),
+    })
+    .await;
+    let logger = RemoteLogger::start(
+        handle(&mock.url).await,
+        LoggerConfig {
+            retry_after: Duration::from_millis(1000),
+            ..Default::default()
+        },
+    );
+    logger.log("first");
+    for i in 0..5 {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        logger.log(format!("message {}", i));
+    }
+    assert_eq!(logged(&mock), ["first"]);
+    tokio::time::sleep(Duration::from_millis(800)).await;
+    assert_eq!(logged(&mock), ["first", "first"]);
+    logger.shutdown().await;
+    assert_eq!(logger.pending(), 6);
+}
+
+#[tokio::test]
+async fn rejected_messages_are_dropped() {
+    let mock = mock_api("secret", api).await;
+    let logger = RemoteLogger::start(
+        handle(&mock.url).await,
+        LoggerConfig {
+            retry_after: Duration::from_secs(60),
+            ..Default::default()
+        },
+    );
+    logger.log("first");
+    logger.log("too long");
+    logger.log("second");
+    wait_until_sent(&logger).await;
+    logger.shutdown().await;
+
+    assert_eq!(logger.rejected(), 1);
+    assert_eq!(logged(&mock), ["first", "too long", "second"]);
+}
+
+#[cfg(feature = "log")]
+#[tokio::test]
+async fn log_records_at_the_level_are_forwarded() {
+    let mock = mock_api("secret", api).await;
+    let logger = RemoteLogger::start(handle(&mock.url).await, LoggerConfig::default());
+    logger.install().unwrap();
+
+    log::info!("not sent");
+    log::warn!("disk almost full");
+    wait_until_sent(&logger).await;
+    logger.shutdown().await;
+    assert_eq!(logged(&mock), ["[WARN] logger: disk almost full"]);
+}
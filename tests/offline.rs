@@ -0,0 +1,154 @@
+#![cfg(feature = "v1_2")]
+
+mod common;
+
+use common::{free_addr, init_response, mock_api, Params};
+use keyauth_obf::offline::OfflineConfig;
+use keyauth_obf::v1_2::KeyauthApi;
+use serde_json::{json, Value};
+use std::path::PathBuf;
+
+fn api(params: &Params) -> Value {
+    match params["type"].as_str() {
+        "init" => init_response(),
+        "license" if params["key"] == "KEY-1234" => json!({
+            "success": true,
+            "message": "Logged in!",
+            "info": {
+                "username": "bob",
+                "subscriptions": [{ "subscription": "default", "expiry": "4102444800" }]
+            }
+        }),
+        "license" if params["key"] == "KEY-OLD" => json!({
+            "success": true,
+            "message": "Logged in!",
+            "info": {
+                "username": "bob",
+                "subscriptions": [{ "subscription": "default", "expiry": "946684800" }]
+            }
+        }),
+        "license" if params["key"] == "KEY-HWID" => {
+            json!({ "success": false, "message": "HWID doesn't match" })
+        }
+        "license" => json!({ "success": false, "message": "Key is banned" }),
+        _ => json!({ "success": false, "message": "Unhandled" }),
+    }
+}
+
+fn snapshot_file(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("keyauth-{}-{}.json", name, std::process::id()))
+}
+
+fn client(url: &str, snapshot_file: &PathBuf) -> KeyauthApi {
+    KeyauthApi::new("app", "owner", "secret", "1.0", url)
+        .with_offline(OfflineConfig::new(snapshot_file))
+}
+
+async fn online_license(snapshot_file: &PathBuf, key: &str) -> Result<(), String> {
+    let mock = mock_api("secret", api).await;
+    let mut auth = client(&mock.url, snapshot_file);
+    auth.init(None).await.inner().unwrap();
+    auth.license(key.to_string(), Some("hwid-1".to_string()))
+        .await
+        .inner()
+        .map(|_| ())
+}
+
+#[tokio::test]
+async fn last_login_is_used_while_offline() {
+    let file = snapshot_file("offline-login");
+    online_license(&file, "KEY-1234").await.unwrap();
+
+    let mut offline = client(&format!("http://{}/", free_addr()), &file);
+    assert!(offline.init(None).await.inner().is_err());
+    let session = offline
+        .offline_login("KEY-1234".to_string(), Some("hwid-1".to_string()))
+        .inner()
+        .unwrap();
+    assert_eq!(session.username, "bob");
+    assert_eq!(session.subscriptions[0].subscription, "default");
+    assert!(offline
+        .offline_login("KEY-5678".to_string(), Some("hwid-1".to_string()))
+        .inner()
+        .is_err());
+    assert!(offline
+        .offline_login("KEY-1234".to_string(), Some("hwid-2".to_string()))
+        .inner()
+        .is_err());
+
+    // a login rejected for something that can change keeps the snapshot, a banned key removes it
+    assert!(online_license(&file, "KEY-HWID").await.is_err());
+    assert!(file.exists());
+    assert!(online_license(&file, "KEY-0000").await.is_err());
+    assert!(!file.exists());
+    assert!(offline
+        .offline_login("KEY-1234".to_string(), Some("hwid-1".to_string()))
+        .inner()
+        .is_err());
+}
+
+#[tokio::test]
+async fn edited_snapshots_are_rejected() {
+    let file = snapshot_file("offline-edited");
+    online_license(&file, "KEY-1234").await.unwrap();
+    let saved: Value = serde_json::from_slice(&std::fs::read(&file).unwrap()).unwrap();
+    let mut auth = client(&format!("http://{}/", free_addr()), &file);
+
+    let mut moved = saved.clone();
+    moved["hwid"] = json!("hwid-2");
+    std::fs::write(&file, moved.to_string()).unwrap();
+    assert!(auth
+        .offline_login("KEY-1234".to_string(), Some("hwid-2".to_string()))
+        .inner()
+        .is_err());
+
+    let mut younger = saved.clone();
+    younger["saved_at"] = json!(saved["saved_at"].as_u64().unwrap() + 3600);
+    std::fs::write(&file, younger.to_string()).unwrap();
+    assert!(auth
+        .offline_login("KEY-1234".to_string(), Some("hwid-1".to_string()))
+        .inner()
+        .is_err());
+
+    let mut body = saved.clone();
+    body["body"] = json!(saved["body"].as_str().unwrap().replace("bob", "eve"));
+    std::fs::write(&file, body.to_string()).unwrap();
+    assert!(auth
+        .offline_login("KEY-1234".to_string(), Some("hwid-1".to_string()))
+        .inner()
+        .is_err());
+
+    std::fs::write(&file, saved.to_string()).unwrap();
+    assert!(auth
+        .offline_login("KEY-1234".to_string(), Some("hwid-1".to_string()))
+        .inner()
+        .is_ok());
+    let _ = std::fs::remove_file(&file);
+}
+
+#[tokio::test]
+async fn license_key_isnt_saved_in_plaintext() {
+    let file = snapshot_file("offline-plaintext");
+    online_license(&file, "KEY-1234").await.unwrap();
+    let saved = std::fs::read_to_string(&file).unwrap();
+    assert!(!saved.contains("KEY-1234"));
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&file).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+    let _ = std::fs::remove_file(&file);
+}
+
+#[tokio::test]
+async fn snapshots_without_time_left_are_rejected() {
+    let file = snapshot_file("offline-expired-sub");
+    online_license(&file, "KEY-OLD").await.unwrap();
+    let mut auth = client(&format!("http://{}/", free_addr()), &file);
+    assert!(auth
+        .offline_login("KEY-OLD".to_string(), Some("hwid-1".to_string()))
+        .inner()
+        .is_err());
+    let _ = std::fs::remove_file(&file);
+}
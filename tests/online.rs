@@ -0,0 +1,71 @@
+use keyauth_obf::online::{OnlineUser, PresenceEvent, PresenceTracker};
+
+fn users(names: &[&str]) -> Vec<OnlineUser> {
+    names
+        .iter()
+        .map(|name| OnlineUser {
+            credential: name.to_string(),
+        })
+        .collect()
+}
+
+fn sorted(mut events: Vec<PresenceEvent>) -> Vec<PresenceEvent> {
+    events.sort_by_key(|event| match event {
+        PresenceEvent::Left(user) => (0, user.credential.clone()),
+        PresenceEvent::Joined(user) => (1, user.credential.clone()),
+    });
+    events
+}
+
+#[test]
+fn tracker_reports_joins_and_leaves() {
+    let mut tracker = PresenceTracker::new(3);
+    assert_eq!(tracker.count(), 3);
+    assert!(tracker.users().is_empty());
+
+    // the first snapshot is the baseline
+    assert!(tracker.update(users(&["alice", "bob"])).is_empty());
+    assert_eq!(tracker.count(), 2);
+
+    assert_eq!(
+        sorted(tracker.update(users(&["bob", "carol"]))),
+        [
+            PresenceEvent::Left(users(&["alice"]).remove(0)),
+            PresenceEvent::Joined(users(&["carol"]).remove(0)),
+        ]
+    );
+    assert!(tracker.update(users(&["carol", "bob"])).is_empty());
+    assert_eq!(
+        sorted(tracker.update(Vec::new())),
+        [
+            PresenceEvent::Left(users(&["bob"]).remove(0)),
+            PresenceEvent::Left(users(&["carol"]).remove(0)),
+        ]
+    );
+    assert_eq!(tracker.count(), 0);
+}
+
+#[test]
+fn count_is_the_number_of_sessions() {
+    let mut tracker = PresenceTracker::default();
+    tracker.update(users(&["alice"]));
+
+    // a second session of the same user is counted but isnt a join
+    assert_eq!(
+        tracker.update(users(&["alice", "alice", "bob"])),
+        [PresenceEvent::Joined(users(&["bob"]).remove(0))]
+    );
+    assert_eq!(tracker.count(), 3);
+    assert_eq!(tracker.users().len(), 2);
+
+    // closing one of the two sessions isnt a leave
+    assert_eq!(
+        tracker.update(users(&["alice", "bob"])),
+        Vec::<PresenceEvent>::new()
+    );
+    assert_eq!(tracker.count(), 2);
+    assert_eq!(
+        tracker.update(users(&["bob"])),
+        [PresenceEvent::Left(users(&["alice"]).remove(0))]
+    );
+}
@@ -0,0 +1,372 @@
+#![cfg(feature = "seller")]
+
+mod common;
+
+use common::{free_addr, mock_api, Params};
+use keyauth_obf::seller::{
+    BlacklistKind, GenerateKeys, KeyCharacters, License, SellerApi, WebhookOptions,
+};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+fn api(params: &Params) -> Value {
+    if params["sellerkey"] != "seller-key" {
+        return json!({ "success": false, "message": "Seller key not found" });
+    }
+    match params["type"].as_str() {
+        "add" if params["amount"] == "1" => json!({ "success": true, "key": "AAAA-BBBB" }),
+        "add" => json!({ "success": true, "keys": ["AAAA-1111", "AAAA-2222"] }),
+        "fetchallkeys" if params["format"] == "text" => json!("AAAA-1111\nAAAA-2222"),
+        "fetchallkeys" => json!({
+            "success": true,
+            "keys": [
+                { "id": 1, "key": "AAAA-1111", "note": null, "expires": "86400", "status": "Not Used", "level": 1, "genby": "SellerAPI", "gendate": "1660000000", "usedon": null, "usedby": null, "banned": null },
+                { "id": "2", "key": "AAAA-2222", "expires": "86400", "status": "Used", "level": "2", "usedby": "bob" }
+            ]
+        }),
+        "verify" if params["key"] == "AAAA-1111" => {
+            json!({ "success": true, "message": "Key exists" })
+        }
+        "verify" => json!({ "success": false, "message": "Key not found" }),
+        "ban" => json!({ "success": true, "message": "Successfully banned license" }),
+        _ => json!({ "success": false, "message": "Unhandled Type" }),
+    }
+}
+
+#[tokio::test]
+async fn generate_keys_sends_options() {
+    let mock = mock_api("", api).await;
+    let seller = SellerApi::new("seller-key", &mock.url);
+
+    let keys = seller
+        .generate_keys(&GenerateKeys {
+            amount: 2,
+            expiry_days: 30,
+            level: 3,
+            mask: "XXXX-****".to_string(),
+            characters: KeyCharacters::Uppercase,
+            note: Some("giveaway".to_string()),
+            ..Default::default()
+        })
+        .await
+        .inner()
+        .unwrap();
+    assert_eq!(keys, vec!["AAAA-1111", "AAAA-2222"]);
+
+    let add = mock.last("add").unwrap();
+    assert_eq!(add["expiry"], "30");
+    assert_eq!(add["level"], "3");
+    assert_eq!(add["mask"], "XXXX-****");
+    assert_eq!(add["character"], "2");
+    assert_eq!(add["note"], "giveaway");
+
+    let key = seller.generate_keys(&GenerateKeys::default()).await.inner();
+    assert_eq!(key, Ok(vec!["AAAA-BBBB".to_string()]));
+}
+
+#[tokio::test]
+async fn fetch_and_export_keys() {
+    let mock = mock_api("", api).await;
+    let seller = SellerApi::new("seller-key", &mock.url);
+
+    let keys: Vec<License> = seller.fetch_all_keys().await.inner().unwrap();
+    assert_eq!(keys.len(), 2);
+    assert_eq!(keys[0].id, "1");
+    assert_eq!(keys[0].level, "1");
+    assert_eq!(keys[0].note, "");
+    assert_eq!(keys[1].usedby, "bob");
+
+    let export = seller.export_keys().await.inner().unwrap();
+    assert_eq!(export.lines().count(), 2);
+}
+
+#[tokio::test]
+async fn verify_ban_and_errors() {
+    let mock = mock_api("", api).await;
+    let seller = SellerApi::new("seller-key", &mock.url);
+
+    assert_eq!(seller.verify_key("AAAA-1111").await.inner(), Ok(true));
+    assert_eq!(seller.verify_key("nope").await.inner(), Ok(false));
+    assert_eq!(
+        seller
+            .ban_key("AAAA-1111", "chargeback", true)
+            .await
+            .inner(),
+        Ok("Successfully banned license".to_string())
+    );
+    let ban = mock.last("ban").unwrap();
+    assert_eq!(ban["reason"], "chargeback");
+    assert_eq!(ban["userToo"], "1");
+
+    assert_eq!(
+        seller.unban_key("AAAA-1111").await.inner(),
+        Err("Unhandled Type".to_string())
+    );
+    let wrong_key = SellerApi::new("wrong", &mock.url);
+    assert_eq!(
+        wrong_key.fetch_all_keys().await.inner(),
+        Err("Seller key not found".to_string())
+    );
+}
+
+#[tokio::test]
+async fn users_and_subscriptions() {
+    let mock = mock_api("", |params: &Params| match params["type"].as_str() {
+        "userdata" if params["user"] == "bob" => json!({
+            "success": true,
+            "username": "bob",
+            "subscriptions": [{ "subscription": "default", "key": "AAAA-1111", "expiry": "1700000000" }],
+            "ip": "127.0.0.1",
+            "hwid": "hwid",
+            "createdate": 1660000000,
+            "lastlogin": "1660000001",
+            "banned": null
+        }),
+        "userdata" => json!({ "success": false, "message": "User not found" }),
+        "fetchallsubs" => json!({ "success": true, "subs": [{ "name": "default", "level": "1" }] }),
+        "extend" => json!({ "success": true, "message": "Successfully extended user(s)" }),
+        _ => json!({ "success": false, "message": "Unhandled Type" }),
+    })
+    .await;
+    let seller = SellerApi::new("seller-key", &mock.url);
+
+    let user = seller.fetch_user("bob").await.inner().unwrap();
+    assert_eq!(user.createdate, "1660000000");
+    assert_eq!(user.banned, "");
+    assert_eq!(user.subscriptions[0].key, "AAAA-1111");
+    assert_eq!(
+        seller.fetch_user("alice").await.inner(),
+        Err("User not found".to_string())
+    );
+
+    let subs = seller.fetch_all_subscriptions().await.inner().unwrap();
+    assert_eq!(subs[0].name, "default");
+
+    seller
+        .extend_subscription("all", "default", 7, true)
+        .await
+        .inner()
+        .unwrap();
+    let extend = mock.last("extend").unwrap();
+    assert_eq!(extend["user"], "all");
+    assert_eq!(extend["expiry"], "7");
+    assert_eq!(extend["activeOnly"], "1");
+}
+
+#[tokio::test]
+async fn files_vars_and_webhooks() {
+    let mock = mock_api("", |params: &Params| match params["type"].as_str() {
+        "delfile" => json!({ "success": true, "message": "Successfully deleted file" }),
+        "upload" => {
+            json!({ "success": true, "message": "Successfully uploaded file", "id": 123456 })
+        }
+        "addvar" => json!({ "success": true, "message": "Successfully created variable" }),
+        "addwebhook" => {
+            json!({ "success": true, "message": "Webhook added successfully", "webid": "abc123" })
+        }
+        "editwebhook" => json!({ "success": true, "message": "Webhook updated" }),
+        _ => json!({ "success": false, "message": "Unhandled Type" }),
+    })
+    .await;
+    let seller = SellerApi::new("seller-key", &mock.url);
+
+    let id = seller
+        .replace_file("654321", "https://example.com/build.exe", true)
+        .await
+        .inner()
+        .unwrap();
+    assert_eq!(id, "123456");
+    assert_eq!(mock.last("delfile").unwrap()["fileid"], "654321");
+    // the old file is only deleted once the new one is there
+    let types: Vec<String> = mock
+        .requests()
+        .into_iter()
+        .map(|p| p["type"].clone())
+        .collect();
+    assert_eq!(types, ["upload", "delfile"]);
+    let upload = mock.last("upload").unwrap();
+    assert_eq!(upload["url"], "https://example.com/build.exe");
+    assert_eq!(upload["authed"], "1");
+
+    seller
+        .create_var("latest", "1.2.3", false)
+        .await
+        .inner()
+        .unwrap();
+    let addvar = mock.last("addvar").unwrap();
+    assert_eq!(addvar["data"], "1.2.3");
+    assert_eq!(addvar["authed"], "0");
+
+    let options = WebhookOptions {
+        base_url: "https://example.com/hook?".to_string(),
+        authed: false,
+        ..Default::default()
+    };
+    let webid = seller.create_webhook(&options).await.inner().unwrap();
+    assert_eq!(webid, "abc123");
+    seller.edit_webhook(&webid, &options).await.inner().unwrap();
+    let edit = mock.last("editwebhook").unwrap();
+    assert_eq!(edit["webid"], "abc123");
+    assert_eq!(edit["baseurl"], "https://example.com/hook?");
+    assert_eq!(edit["ua"], "KeyAuth");
+    assert_eq!(edit["authed"], "0");
+}
+
+#[tokio::test]
+async fn files_are_streamed_before_keyauth_downloads_them() {
+    let storage = mock_api("", |_: &Params| json!("")).await;
+    let mock = mock_api("", |params: &Params| match params["type"].as_str() {
+        "upload" => {
+            json!({ "success": true, "message": "Successfully uploaded file", "id": "42" })
+        }
+        _ => json!({ "success": false, "message": "Unhandled Type" }),
+    })
+    .await;
+    let seller = SellerApi::new("seller-key", &mock.url);
+    let path = std::env::temp_dir().join(format!("keyauth-build-{}.bin", std::process::id()));
+    std::fs::write(&path, "build-contents").unwrap();
+
+    let put_url = format!("{}build.bin", storage.url);
+    let id = seller
+        .upload_path(&put_url, "https://example.com/build.bin", &path, false)
+        .await
+        .inner()
+        .unwrap();
+    assert_eq!(id, "42");
+    assert_eq!(storage.bodies(), ["build-contents"]);
+    assert_eq!(
+        mock.last("upload").unwrap()["url"],
+        "https://example.com/build.bin"
+    );
+    let _ = std::fs::remove_file(&path);
+}
+
+#[tokio::test]
+async fn missing_ids_are_errors() {
+    let mock = mock_api("", |params: &Params| match params["type"].as_str() {
+        "upload" => json!({ "success": true, "message": "Successfully uploaded file" }),
+        "addwebhook" => json!({ "success": true, "message": "Webhook added successfully" }),
+        _ => json!({ "success": false, "message": "Unhandled Type" }),
+    })
+    .await;
+    let seller = SellerApi::new("seller-key", &mock.url);
+    assert!(seller
+        .upload_file("https://example.com/build.exe", true)
+        .await
+        .inner()
+        .is_err());
+    assert!(seller
+        .replace_file("654321", "https://example.com/build.exe", true)
+        .await
+        .inner()
+        .is_err());
+    assert!(mock.last("delfile").is_none());
+    assert!(seller
+        .create_webhook(&WebhookOptions::default())
+        .await
+        .inner()
+        .is_err());
+}
+
+#[tokio::test]
+async fn blacklists_sessions_and_chat() {
+    let mock = mock_api("", |params: &Params| match params["type"].as_str() {
+        "black" | "delblack" | "kill" | "muteuser" => json!({ "success": true, "message": "ok" }),
+        "fetchallsessions" => json!({
+            "success": true,
+            "sessions": [{ "id": "abc", "credential": "bob", "expiry": 1700000000, "validated": 1, "ip": "127.0.0.1" }]
+        }),
+        _ => json!({ "success": false, "message": "Unhandled Type" }),
+    })
+    .await;
+    let seller = SellerApi::new("seller-key", &mock.url);
+
+    seller
+        .add_blacklist(BlacklistKind::Hwid, "bad-hwid")
+        .await
+        .inner()
+        .unwrap();
+    assert_eq!(mock.last("black").unwrap()["hwid"], "bad-hwid");
+    seller
+        .remove_blacklist(BlacklistKind::Ip, "1.2.3.4")
+        .await
+        .inner()
+        .unwrap();
+    let delblack = mock.last("delblack").unwrap();
+    assert_eq!(delblack["data"], "1.2.3.4");
+    assert_eq!(delblack["blacktype"], "ip");
+
+    let sessions = seller.fetch_all_sessions().await.inner().unwrap();
+    assert_eq!(sessions[0].credential, "bob");
+    assert_eq!(sessions[0].validated, "1");
+    seller.kill_session(&sessions[0].id).await.inner().unwrap();
+    assert_eq!(mock.last("kill").unwrap()["sessid"], "abc");
+
+    seller
+        .mute_user("bob", Duration::from_secs(3600))
+        .await
+        .inner()
+        .unwrap();
+    assert_eq!(mock.last("muteuser").unwrap()["time"], "3600");
+}
+
+#[tokio::test]
+async fn settings_and_stats() {
+    let mock = mock_api("", |params: &Params| match params["type"].as_str() {
+        "getsettings" => json!({ "success": true, "enabled": true, "hwidcheck": "1", "hashcheck": 0, "version": "1.0", "download": "", "sessionexpiry": 21600 }),
+        "updatesettings" | "addhash" => json!({ "success": true, "message": "ok" }),
+        "stats" => json!({ "success": true, "totalkeys": "10", "unused": 4, "used": "6", "totalusers": 6 }),
+        _ => json!({ "success": false, "message": "Unhandled Type" }),
+    })
+    .await;
+    let seller = SellerApi::new("seller-key", &mock.url);
+
+    let settings = seller.get_settings().await.inner().unwrap();
+    assert!(settings.enabled);
+    assert!(settings.hwidcheck);
+    assert!(!settings.hashcheck);
+    assert_eq!(settings.version, "1.0");
+    assert_eq!(settings.sessionexpiry, "21600");
+
+    seller.set_version("1.1").await.inner().unwrap();
+    let update = mock.last("updatesettings").unwrap();
+    assert_eq!(update["ver"], "1.1");
+    assert!(!update.contains_key("hwidcheck"));
+    seller
+        .add_hash("d41d8cd98f00b204e9800998ecf8427e")
+        .await
+        .inner()
+        .unwrap();
+    assert_eq!(
+        mock.last("addhash").unwrap()["hash"],
+        "d41d8cd98f00b204e9800998ecf8427e"
+    );
+
+    let stats = seller.fetch_stats().await.inner().unwrap();
+    assert_eq!(stats.totalkeys, 10);
+    assert_eq!(stats.unused, 4);
+    assert_eq!(stats.banned, 0);
+}
+
+#[tokio::test]
+async fn network_errors_leave_the_secret_urls_out() {
+    let seller = SellerApi::new("seller-key", &format!("http://{}/", free_addr()));
+    let err = seller.verify_key("AAAA-1111").await.inner().unwrap_err();
+    assert!(!err.contains("seller-key"), "{}", err);
+
+    let put_url = format!(
+        "http://{}/build.bin?X-Amz-Signature=put-signature",
+        free_addr()
+    );
+    let err = seller
+        .upload_reader(
+            &put_url,
+            "https://example.com/build.bin",
+            &b"build"[..],
+            false,
+        )
+        .await
+        .inner()
+        .unwrap_err();
+    assert!(!err.contains("put-signature"), "{}", err);
+}
@@ -0,0 +1,96 @@
+#![cfg(all(feature = "v1_2", feature = "tracing"))]
+
+mod common;
+
+use common::{init_response, mock_api, Params};
+use keyauth_obf::v1_2::KeyauthApi;
+use serde_json::{json, Value};
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use tracing_subscriber::fmt::format::FmtSpan;
+
+fn api(params: &Params) -> Value {
+    match params["type"].as_str() {
+        "init" => init_response(),
+        "login" => json!({ "success": false, "message": "Invalid password" }),
+        "var" => json!({ "success": true, "message": "var-value-1234" }),
+        _ => json!({ "success": false, "message": "Unhandled" }),
+    }
+}
+
+#[derive(Clone, Default)]
+struct Output(Arc<Mutex<Vec<u8>>>);
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Output {
+    fn take(&self) -> String {
+        String::from_utf8(std::mem::take(&mut *self.0.lock().unwrap())).unwrap()
+    }
+}
+
+#[tokio::test]
+async fn requests_are_traced_without_secrets() {
+    let output = Output::default();
+    let writer = output.clone();
+    let subscriber = tracing_subscriber::fmt()
+        .with_writer(move || writer.clone())
+        .with_ansi(false)
+        .with_span_events(FmtSpan::CLOSE)
+        .finish();
+    let _guard = tracing::subscriber::set_default(subscriber);
+
+    let mock = mock_api("app-secret", api).await;
+    let mut auth = KeyauthApi::new("my-app", "owner-1234", "app-secret", "1.0", &mock.url);
+    auth.init(None).await.inner().unwrap();
+    let _ = auth
+        .login("bob".to_string(), "hunter2".to_string(), None, None)
+        .await;
+    let logs = output.take();
+    assert!(logs.contains(r#"type="login""#), "{}", logs);
+    assert!(logs.contains("username=bob pass=<redacted>"), "{}", logs);
+    assert!(logs.contains(r#"error="rejected""#), "{}", logs);
+    assert!(logs.contains(r#"reason="Invalid password""#), "{}", logs);
+    assert!(logs.contains("latency_ms="), "{}", logs);
+    assert_eq!(
+        auth.var("download".to_string()).await.inner(),
+        Ok("var-value-1234".to_string())
+    );
+    let logs = logs + &output.take();
+    assert!(logs.contains(r#"type="var""#), "{}", logs);
+    assert!(logs.contains("success=true"), "{}", logs);
+    for secret in [
+        "hunter2",
+        "owner-1234",
+        "app-secret",
+        "my-app",
+        "var-value-1234",
+    ] {
+        assert!(!logs.contains(secret), "{} in {}", secret, logs);
+    }
+
+    // signed with a different secret
+    let mock = mock_api("other-secret", api).await;
+    let mut auth = KeyauthApi::new("my-app", "owner-1234", "app-secret", "1.0", &mock.url);
+    assert!(auth.init(None).await.inner().is_err());
+    let logs = output.take();
+    assert!(logs.contains(r#"type="init""#), "{}", logs);
+    assert!(logs.contains(r#"error="tampered""#), "{}", logs);
+    assert!(logs.contains("keyauth request failed"), "{}", logs);
+
+    // signed but not json
+    let mock = mock_api("app-secret", |_| json!("<html>502 Bad Gateway</html>")).await;
+    let mut auth = KeyauthApi::new("my-app", "owner-1234", "app-secret", "1.0", &mock.url);
+    assert!(auth.init(None).await.inner().is_err());
+    let logs = output.take();
+    assert!(logs.contains(r#"error="invalid_response""#), "{}", logs);
+}
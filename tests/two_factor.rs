@@ -0,0 +1,133 @@
+#![cfg(feature = "v1_2")]
+
+mod common;
+
+use common::{free_addr, init_response, mock_api, Params};
+use keyauth_obf::two_factor::{LoginError, TwoFactorSetup};
+use keyauth_obf::v1_2::KeyauthApi;
+use serde_json::{json, Value};
+
+fn api(params: &Params) -> Value {
+    match params["type"].as_str() {
+        "init" => init_response(),
+        "login" => match params.get("code").map(String::as_str) {
+            None => json!({ "success": false, "message": "2FA code required" }),
+            Some("123456") => json!({
+                "success": true,
+                "message": "Logged in!",
+                "info": {
+                    "ip": "127.0.0.1",
+                    "createdate": "1660000000",
+                    "lastlogin": "1660000001",
+                    "subscriptions": [{ "subscription": "default" }]
+                }
+            }),
+            Some(_) => json!({ "success": false, "message": "Invalid 2FA code" }),
+        },
+        "2faenable" if params.contains_key("code") => {
+            json!({ "success": true, "message": "2FA has been enabled" })
+        }
+        "2faenable" => json!({
+            "success": true,
+            "message": "2FA secret generated",
+            "2fa": { "secret_code": "JBSWY3DPEHPK3PXP" }
+        }),
+        "2fadisable" => json!({ "success": false, "message": "Invalid 2FA code" }),
+        _ => json!({ "success": false, "message": "Unhandled" }),
+    }
+}
+
+#[tokio::test]
+async fn login_asks_for_the_code_and_accepts_it_on_retry() {
+    let mock = mock_api("secret", api).await;
+    let mut auth = KeyauthApi::new("my app", "owner", "secret", "1.0", &mock.url);
+    auth.init(None).await.inner().unwrap();
+
+    for (code, err) in [
+        (None, LoginError::TwoFactorRequired),
+        (Some("000000"), LoginError::InvalidTwoFactorCode),
+    ] {
+        let res = auth
+            .login(
+                "bob".to_string(),
+                "password".to_string(),
+                None,
+                code.map(str::to_string),
+            )
+            .await;
+        assert_eq!(res.inner(), Err(err));
+    }
+
+    auth.login(
+        "bob".to_string(),
+        "password".to_string(),
+        None,
+        Some("123456".to_string()),
+    )
+    .await
+    .inner()
+    .unwrap();
+    assert_eq!(mock.last("login").unwrap()["code"], "123456");
+    assert_eq!(auth.username, "bob");
+}
+
+#[tokio::test]
+async fn enrollment_returns_the_secret_and_uri() {
+    let mock = mock_api("secret", api).await;
+    let mut auth = KeyauthApi::new("my app", "owner", "secret", "1.0", &mock.url);
+    auth.init(None).await.inner().unwrap();
+    auth.username = "bob".to_string();
+
+    assert_eq!(
+        auth.enable_2fa().await.inner(),
+        Ok(TwoFactorSetup {
+            secret: "JBSWY3DPEHPK3PXP".to_string(),
+            uri: "otpauth://totp/my%20app:bob?secret=JBSWY3DPEHPK3PXP&issuer=my+app".to_string(),
+        })
+    );
+    auth.confirm_2fa("123456".to_string())
+        .await
+        .inner()
+        .unwrap();
+    assert_eq!(mock.last("2faenable").unwrap()["code"], "123456");
+    assert_eq!(
+        auth.disable_2fa("000000".to_string()).await.inner(),
+        Err("Invalid 2FA code".to_string())
+    );
+}
+
+#[tokio::test]
+async fn tampered_responses_and_network_errors_are_returned() {
+    let mock = mock_api("secret", api).await;
+    let mut auth = KeyauthApi::new("my app", "owner", "secret", "1.0", &mock.url);
+    auth.init(None).await.inner().unwrap();
+
+    // never saw the init, so it signs with a session key the client didnt send
+    let other = mock_api("secret", api).await;
+    auth.api_url = other.url.clone();
+    assert_eq!(
+        auth.login(
+            "bob".to_string(),
+            "password".to_string(),
+            None,
+            Some("123456".to_string()),
+        )
+        .await
+        .inner(),
+        Err(LoginError::Tampered)
+    );
+    assert!(auth.enable_2fa().await.inner().is_err());
+
+    auth.api_url = format!("http://{}/", free_addr());
+    assert!(auth.enable_2fa().await.inner().is_err());
+    assert!(auth
+        .confirm_2fa("123456".to_string())
+        .await
+        .inner()
+        .is_err());
+    assert!(auth
+        .disable_2fa("123456".to_string())
+        .await
+        .inner()
+        .is_err());
+}
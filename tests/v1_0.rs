@@ -0,0 +1,140 @@
+#![cfg(feature = "v1_0")]
+
+mod common;
+
+use aes::cipher::block_padding::Pkcs7;
+use aes::cipher::{BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use aes::Aes256;
+use common::{init_response, mock_raw, MockApi, Params};
+use hmac_sha256::Hash;
+use keyauth_obf::two_factor::LoginError;
+use keyauth_obf::v1_0::KeyauthApi;
+use serde_json::{json, Value};
+use std::sync::Mutex;
+
+/// sent hex encoded, everything else but the hash and iv is encrypted
+const PLAIN_FIELDS: [&str; 4] = ["type", "sessionid", "name", "ownerid"];
+
+fn derive(key: &str, iv: &str) -> ([u8; 32], [u8; 16]) {
+    let mut aes_key = [0u8; 32];
+    let mut aes_iv = [0u8; 16];
+    aes_key.copy_from_slice(&hex::encode(Hash::hash(key.as_bytes())).as_bytes()[..32]);
+    aes_iv.copy_from_slice(&hex::encode(Hash::hash(iv.as_bytes())).as_bytes()[..16]);
+    (aes_key, aes_iv)
+}
+
+fn encrypt(message: &str, key: &str, iv: &str) -> String {
+    let (aes_key, aes_iv) = derive(key, iv);
+    hex::encode(
+        cbc::Encryptor::<Aes256>::new(&aes_key.into(), &aes_iv.into())
+            .encrypt_padded_vec_mut::<Pkcs7>(message.as_bytes()),
+    )
+}
+
+fn decrypt(message: &str, key: &str, iv: &str) -> String {
+    let (aes_key, aes_iv) = derive(key, iv);
+    let plaintext = cbc::Decryptor::<Aes256>::new(&aes_key.into(), &aes_iv.into())
+        .decrypt_padded_vec_mut::<Pkcs7>(&hex::decode(message).unwrap())
+        .unwrap();
+    String::from_utf8(plaintext).unwrap()
+}
+
+fn api(params: &Params) -> Value {
+    match params["type"].as_str() {
+        "init" => init_response(),
+        "login" if params["pass"] == "hunter2" => json!({
+            "success": true,
+            "message": "Logged in!",
+            "info": { "username": "bob", "subscriptions": [{ "subscription": "default" }] }
+        }),
+        "login" => json!({ "success": false, "message": "Invalid password" }),
+        "var" => json!({ "success": true, "message": format!("value of {}", params["varid"]) }),
+        _ => json!({ "success": false, "message": "Unhandled" }),
+    }
+}
+
+/// a 1.0 server: decrypts the request with the secret (init) or the session key, hands the plaintext to `api` and
+/// encrypts the answer with `response_secret`
+async fn mock_v1_0(secret: &'static str, response_secret: &'static str) -> MockApi {
+    let enckey = Mutex::new(String::new());
+    mock_raw(move |raw| {
+        let iv = &raw["init_iv"];
+        let init = raw["type"] == hex::encode("init");
+        let key = if init {
+            secret.to_string()
+        } else {
+            enckey.lock().unwrap().clone()
+        };
+        let mut params = Params::new();
+        for (field, value) in raw {
+            let value = if PLAIN_FIELDS.contains(&field.as_str()) {
+                String::from_utf8(hex::decode(value).unwrap()).unwrap()
+            } else if field == "hash" || field == "init_iv" {
+                value.clone()
+            } else {
+                decrypt(value, &key, iv)
+            };
+            params.insert(field.clone(), value);
+        }
+        let response_key = if init {
+            *enckey.lock().unwrap() = params["enckey"].clone();
+            response_secret.to_string()
+        } else {
+            key
+        };
+        (
+            Vec::new(),
+            encrypt(&api(&params).to_string(), &response_key, iv),
+        )
+    })
+    .await
+}
+
+#[tokio::test]
+async fn requests_and_responses_are_encrypted() {
+    let mock = mock_v1_0("app-secret", "app-secret").await;
+    let mut auth = KeyauthApi::new("my-app", "owner-1234", "app-secret", "1.0", &mock.url);
+    auth.init(Some("abcd")).await.inner().unwrap();
+    let session = auth
+        .login(
+            "bob".to_string(),
+            "hunter2".to_string(),
+            Some("hwid-1".to_string()),
+            None,
+        )
+        .await
+        .inner()
+        .unwrap();
+    assert_eq!(session.username, "bob");
+    assert_eq!(auth.subscription, "default");
+    assert_eq!(
+        auth.var("motd".to_string()).await.inner().unwrap(),
+        "value of motd"
+    );
+    assert_eq!(
+        auth.login("bob".to_string(), "wrong".to_string(), None, None)
+            .await
+            .inner(),
+        Err(LoginError::Other("Invalid password".to_string()))
+    );
+
+    let requests = mock.requests();
+    let init = &requests[0];
+    assert_eq!(init["type"], hex::encode("init"));
+    assert_eq!(init["name"], hex::encode("my-app"));
+    assert_eq!(init["hash"], "abcd");
+    assert_eq!(init["init_iv"].len(), 64);
+    assert_ne!(init["ver"], "1.0");
+    let login = &requests[1];
+    assert_eq!(login["sessionid"], hex::encode("session"));
+    assert_ne!(login["pass"], "hunter2");
+    assert_ne!(login["init_iv"], init["init_iv"]);
+}
+
+#[tokio::test]
+async fn responses_that_dont_decrypt_are_rejected() {
+    let mock = mock_v1_0("app-secret", "other-secret").await;
+    let mut auth = KeyauthApi::new("my-app", "owner-1234", "app-secret", "1.0", &mock.url);
+    let err = auth.init(None).await.inner().unwrap_err();
+    assert!(err.starts_with("response was tampered with"), "{}", err);
+}
@@ -0,0 +1,100 @@
+#![cfg(feature = "v1_3")]
+
+mod common;
+
+use common::{init_response, mock_raw, MockApi, Params};
+use ed25519_dalek::{Signer, SigningKey};
+use keyauth_obf::two_factor::LoginError;
+use keyauth_obf::v1_3::KeyauthApi;
+use serde_json::{json, Value};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn api(params: &Params) -> Value {
+    match params["type"].as_str() {
+        "init" => init_response(),
+        "login" => json!({
+            "success": true,
+            "message": "Logged in!",
+            "info": { "username": "bob", "subscriptions": [{ "subscription": "default" }] }
+        }),
+        _ => json!({ "success": false, "message": "Unhandled" }),
+    }
+}
+
+/// how the mock signs the login response
+#[derive(Clone, Copy)]
+enum Signing {
+    Valid,
+    /// signs the body, then changes it
+    EditedBody,
+    /// timestamp from a minute ago
+    Stale,
+    /// no x-signature-ed25519 header
+    Missing,
+}
+
+fn signing_key() -> SigningKey {
+    SigningKey::from_bytes(&[7; 32])
+}
+
+/// signs init correctly and the login response as `signing` says
+async fn mock_v1_3(signing: Signing) -> MockApi {
+    mock_raw(move |params| {
+        let mut body = api(params).to_string();
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let login = params["type"] == "login";
+        let timestamp = match signing {
+            Signing::Stale if login => now - 60,
+            _ => now,
+        };
+        let signature = signing_key().sign(format!("{}{}", timestamp, body).as_bytes());
+        if login && matches!(signing, Signing::EditedBody) {
+            body = body.replace("bob", "eve");
+        }
+        let mut headers = vec![("x-signature-timestamp".to_string(), timestamp.to_string())];
+        if !(login && matches!(signing, Signing::Missing)) {
+            headers.push((
+                "x-signature-ed25519".to_string(),
+                hex::encode(signature.to_bytes()),
+            ));
+        }
+        (headers, body)
+    })
+    .await
+}
+
+async fn login(signing: Signing) -> Result<String, LoginError> {
+    let mock = mock_v1_3(signing).await;
+    let public_key = hex::encode(signing_key().verifying_key().to_bytes());
+    let mut auth = KeyauthApi::new("app", "owner", "1.0", &mock.url)
+        .with_public_key(&public_key)
+        .unwrap();
+    auth.init(None).await.inner().unwrap();
+    auth.login("bob".to_string(), "hunter2".to_string(), None, None)
+        .await
+        .inner()
+        .map(|session| session.username)
+}
+
+#[tokio::test]
+async fn signed_responses_are_accepted() {
+    assert_eq!(login(Signing::Valid).await, Ok("bob".to_string()));
+}
+
+#[tokio::test]
+async fn edited_stale_and_unsigned_responses_are_rejected() {
+    assert_eq!(login(Signing::EditedBody).await, Err(LoginError::Tampered));
+    assert_eq!(login(Signing::Stale).await, Err(LoginError::Tampered));
+    assert_eq!(login(Signing::Missing).await, Err(LoginError::Tampered));
+}
+
+#[tokio::test]
+async fn responses_are_checked_against_keyauths_key_by_default() {
+    let mock = mock_v1_3(Signing::Valid).await;
+    let mut auth = KeyauthApi::new("app", "owner", "1.0", &mock.url);
+    let err = auth.init(None).await.inner().unwrap_err();
+    assert!(err.starts_with("response was tampered with"), "{}", err);
+}
@@ -0,0 +1,227 @@
+#![cfg(all(feature = "v1_2", feature = "web_loader"))]
+
+mod common;
+
+use common::{free_addr, init_response, mock_api, Params};
+use keyauth_obf::v1_2::KeyauthApi;
+use keyauth_obf::web_loader::{WebLoaderConfig, WebLoginError};
+use serde_json::{json, Value};
+use std::net::SocketAddr;
+use std::time::Duration;
+
+fn api(params: &Params) -> Value {
+    match params["type"].as_str() {
+        "init" => init_response(),
+        "login" if params["token"] == "good-token" => json!({
+            "success": true,
+            "message": "Logged in!",
+            "info": {
+                "ip": "127.0.0.1",
+                "createdate": "1660000000",
+                "lastlogin": "1660000001",
+                "subscriptions": [{ "subscription": "default" }]
+            }
+        }),
+        "login" if params["token"] == "garbage-token" => json!("<html>502 Bad Gateway</html>"),
+        _ => json!({ "success": false, "message": "Invalid token" }),
+    }
+}
+
+async fn loader_request(
+    addr: SocketAddr,
+    method: reqwest::Method,
+    path: &str,
+) -> reqwest::Response {
+    let client = reqwest::Client::new();
+    loop {
+        match client
+            .request(method.clone(), format!("http://{}{}", addr, path))
+            .send()
+            .await
+        {
+            Ok(resp) => return resp,
+            Err(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+        }
+    }
+}
+
+async fn initialized() -> (common::MockApi, KeyauthApi) {
+    let mock = mock_api("secret", api).await;
+    let mut auth = KeyauthApi::new("app", "owner", "secret", "1.0", &mock.url);
+    auth.init(None).await.inner().unwrap();
+    auth.web_loader = WebLoaderConfig::new(free_addr());
+    (mock, auth)
+}
+
+#[tokio::test]
+async fn web_login_sends_the_given_hwid() {
+    let (mock, mut auth) = initialized().await;
+    let addr = auth.web_loader.addr;
+
+    let (res, resp) = tokio::join!(
+        auth.web_login(Some("custom-hwid".to_string())),
+        loader_request(
+            addr,
+            reqwest::Method::GET,
+            "/handshake?user=bob%20smith&token=good-token"
+        ),
+    );
+
+    let session = res.inner().unwrap();
+    assert_eq!(session.username, "bob smith");
+    assert_eq!(session.hwid, "custom-hwid");
+    assert_eq!(session.create_date, 1660000000);
+    assert_eq!(session.subscription(), "default");
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.headers()["access-control-allow-origin"], "*");
+    assert_eq!(resp.text().await.unwrap(), "Logged in!");
+
+    let login = mock.last("login").unwrap();
+    assert_eq!(login["hwid"], "custom-hwid");
+    assert_eq!(login["username"], "bob smith");
+    assert_eq!(auth.hwid, "custom-hwid");
+    assert_eq!(auth.username, "bob smith");
+    assert_eq!(auth.subscription, "default");
+}
+
+#[tokio::test]
+async fn web_login_form_encodes_the_handshake_values() {
+    let (mock, mut auth) = initialized().await;
+    let addr = auth.web_loader.addr;
+
+    let (res, resp) = tokio::join!(
+        auth.web_login(None),
+        loader_request(
+            addr,
+            reqwest::Method::GET,
+            "/handshake?user=c%2B%2Bdev%26co&token=good-token"
+        ),
+    );
+
+    assert_eq!(res.inner().unwrap().username, "c++dev&co");
+    assert_eq!(resp.status(), 200);
+    let login = mock.last("login").unwrap();
+    assert_eq!(login["username"], "c++dev&co");
+    assert_eq!(login["token"], "good-token");
+}
+
+#[tokio::test]
+async fn web_login_surfaces_rejection() {
+    let (_mock, mut auth) = initialized().await;
+    let addr = auth.web_loader.addr;
+
+    let (res, resp) = tokio::join!(
+        auth.web_login(None),
+        loader_request(
+            addr,
+            reqwest::Method::GET,
+            "/handshake?user=bob&token=bad-token"
+        ),
+    );
+
+    assert_eq!(
+        res.inner(),
+        Err(WebLoginError::Rejected("Invalid token".to_string()))
+    );
+    assert_eq!(resp.status(), 401);
+    assert_eq!(resp.text().await.unwrap(), "Invalid token");
+}
+
+#[tokio::test]
+async fn web_login_answers_preflight_and_rejects_bad_handshake() {
+    let (_mock, mut auth) = initialized().await;
+    let addr = auth.web_loader.addr;
+
+    let requests = async {
+        let preflight = loader_request(addr, reqwest::Method::OPTIONS, "/handshake").await;
+        let other = loader_request(addr, reqwest::Method::GET, "/favicon.ico").await;
+        let handshake = loader_request(addr, reqwest::Method::GET, "/handshake?user=bob").await;
+        (preflight, other, handshake)
+    };
+    let (res, (preflight, other, handshake)) = tokio::join!(auth.web_login(None), requests);
+
+    assert_eq!(res.inner(), Err(WebLoginError::InvalidHandshake));
+    assert_eq!(preflight.status(), 204);
+    assert_eq!(
+        preflight.headers()["access-control-allow-private-network"],
+        "true"
+    );
+    assert_eq!(other.status(), 404);
+    assert_eq!(handshake.status(), 400);
+}
+
+#[tokio::test]
+async fn web_login_times_out() {
+    let (_mock, mut auth) = initialized().await;
+    auth.web_loader.timeout = Some(Duration::from_millis(100));
+
+    assert_eq!(
+        auth.web_login(None).await.inner(),
+        Err(WebLoginError::Timeout)
+    );
+}
+
+#[tokio::test]
+async fn idle_connections_dont_block_the_handshake() {
+    let (_mock, mut auth) = initialized().await;
+    auth.web_loader.read_timeout = Duration::from_secs(30);
+    let addr = auth.web_loader.addr;
+
+    let requests = async {
+        // connections that never send anything, like a browser's speculative preconnects
+        let mut idle = Vec::new();
+        while idle.len() < 2 {
+            match tokio::net::TcpStream::connect(addr).await {
+                Ok(stream) => idle.push(stream),
+                Err(_) => tokio::time::sleep(Duration::from_millis(20)).await,
+            }
+        }
+        let resp = loader_request(
+            addr,
+            reqwest::Method::GET,
+            "/handshake?user=bob&token=good-token",
+        )
+        .await;
+        (idle, resp)
+    };
+    let (res, (_idle, resp)) = tokio::time::timeout(Duration::from_secs(5), async {
+        tokio::join!(auth.web_login(None), requests)
+    })
+    .await
+    .expect("the idle connections held up the handshake");
+
+    assert_eq!(res.inner().unwrap().username, "bob");
+    assert_eq!(resp.status(), 200);
+}
+
+#[tokio::test]
+async fn web_login_tells_bad_responses_and_network_errors_apart() {
+    let (_mock, mut auth) = initialized().await;
+    let addr = auth.web_loader.addr;
+    let (res, resp) = tokio::join!(
+        auth.web_login(None),
+        loader_request(
+            addr,
+            reqwest::Method::GET,
+            "/handshake?user=bob&token=garbage-token"
+        ),
+    );
+    assert_eq!(
+        res.inner(),
+        Err(WebLoginError::InvalidResponse(
+            "<html>502 Bad Gateway</html>".to_string()
+        ))
+    );
+    assert_eq!(resp.status(), 500);
+
+    auth.api_url = format!("http://{}/", free_addr());
+    let (res, _) = tokio::join!(
+        auth.web_login(None),
+        loader_request(
+            addr,
+            reqwest::Method::GET,
+            "/handshake?user=bob&token=good-token"
+        ),
+    );
+    assert!(matches!(res.inner(), Err(WebLoginError::Network(_))));
+}